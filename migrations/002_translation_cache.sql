@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS translation_cache (
+    text_hash CHAR(32) NOT NULL,
+    source_language VARCHAR(10) NOT NULL,
+    target_language VARCHAR(10) NOT NULL,
+    normalized_text TEXT NOT NULL,
+    translated_text TEXT NOT NULL,
+    detected_language VARCHAR(10),
+    confidence REAL,
+    provider VARCHAR(32),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (text_hash, source_language, target_language)
+);
+
+CREATE INDEX idx_translation_cache_created ON translation_cache(created_at);
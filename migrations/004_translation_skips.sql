@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS translation_skips (
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    reason VARCHAR(32) NOT NULL,
+    skip_count BIGINT NOT NULL DEFAULT 0,
+    last_skipped_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (guild_id, channel_id, reason)
+);
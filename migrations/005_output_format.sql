@@ -0,0 +1 @@
+ALTER TABLE translation_channels ADD COLUMN IF NOT EXISTS output_format VARCHAR(16) NOT NULL DEFAULT 'plain';
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS translation_outputs (
+    id BIGSERIAL PRIMARY KEY,
+    channel_id BIGINT NOT NULL REFERENCES translation_channels(channel_id) ON DELETE CASCADE,
+    target_language VARCHAR(10) NOT NULL,
+    webhook_url TEXT NOT NULL,
+    output_format VARCHAR(16) NOT NULL DEFAULT 'plain',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (channel_id, target_language)
+);
+
+CREATE INDEX idx_translation_outputs_channel ON translation_outputs(channel_id);
@@ -0,0 +1,10 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS reaction_translation BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS reaction_translations (
+    message_id BIGINT NOT NULL,
+    target_language VARCHAR(10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (message_id, target_language)
+);
+
+CREATE INDEX idx_reaction_translations_created ON reaction_translations(created_at);
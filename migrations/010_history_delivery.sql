@@ -0,0 +1,6 @@
+ALTER TABLE translation_history ADD COLUMN IF NOT EXISTS source_message_id BIGINT;
+ALTER TABLE translation_history ADD COLUMN IF NOT EXISTS webhook_url TEXT;
+ALTER TABLE translation_history ADD COLUMN IF NOT EXISTS output_format VARCHAR(16) NOT NULL DEFAULT 'plain';
+ALTER TABLE translation_history ADD COLUMN IF NOT EXISTS webhook_message_ids BIGINT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_translation_history_source_message ON translation_history(source_message_id);
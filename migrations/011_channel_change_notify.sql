@@ -0,0 +1,21 @@
+-- Announce changes to translation channels and their outputs so every bot
+-- instance can refresh its in-memory copy. The payload is the channel id.
+CREATE OR REPLACE FUNCTION notify_translation_channel_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM pg_notify('translation_channels_changed', OLD.channel_id::TEXT);
+        RETURN OLD;
+    END IF;
+
+    PERFORM pg_notify('translation_channels_changed', NEW.channel_id::TEXT);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER translation_channels_notify
+    AFTER INSERT OR UPDATE OR DELETE ON translation_channels
+    FOR EACH ROW EXECUTE FUNCTION notify_translation_channel_change();
+
+CREATE TRIGGER translation_outputs_notify
+    AFTER INSERT OR UPDATE OR DELETE ON translation_outputs
+    FOR EACH ROW EXECUTE FUNCTION notify_translation_channel_change();
@@ -0,0 +1,16 @@
+-- Messages waiting for auto-translation, kept only when JOB_PERSISTENT is
+-- on so a crash or restart does not lose them.
+CREATE TABLE IF NOT EXISTS translation_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    author_id BIGINT NOT NULL,
+    author_name VARCHAR(255) NOT NULL,
+    display_name VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    content TEXT NOT NULL,
+    jump_url TEXT NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Characters sent to each translation provider per UTC day, so daily quotas
+-- survive restarts.
+CREATE TABLE IF NOT EXISTS provider_usage (
+    provider VARCHAR(64) NOT NULL,
+    usage_date DATE NOT NULL,
+    characters BIGINT NOT NULL DEFAULT 0,
+    requests BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (provider, usage_date)
+);
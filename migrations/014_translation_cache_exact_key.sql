@@ -0,0 +1,9 @@
+-- Entries were keyed on whitespace-collapsed text, so some belong to a
+-- different message than their key suggests; start over rather than serve them.
+DELETE FROM translation_cache;
+
+ALTER TABLE translation_cache RENAME COLUMN normalized_text TO source_text;
+ALTER TABLE translation_cache ADD COLUMN IF NOT EXISTS allow_llm BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE translation_cache DROP CONSTRAINT translation_cache_pkey;
+ALTER TABLE translation_cache ADD PRIMARY KEY (text_hash, source_language, target_language, allow_llm);
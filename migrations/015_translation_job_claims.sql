@@ -0,0 +1,6 @@
+-- Which running instance owns a persisted job, so a restart or a second
+-- instance sharing the database never queues the same message twice.
+ALTER TABLE translation_jobs ADD COLUMN IF NOT EXISTS claimed_by TEXT;
+ALTER TABLE translation_jobs ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_translation_jobs_claimed_by ON translation_jobs(claimed_by);
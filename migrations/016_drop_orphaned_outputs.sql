@@ -0,0 +1,6 @@
+-- Removing a channel used to leave its extra outputs behind, and setting it
+-- up again brought them back.
+DELETE FROM translation_outputs o
+USING translation_channels c
+WHERE c.channel_id = o.channel_id
+  AND c.is_active = false;
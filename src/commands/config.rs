@@ -0,0 +1,136 @@
+use crate::commands::SetLogCommand;
+use crate::db::{DatabasePool, GuildRepository};
+use crate::services::detect::{is_auto, same_language};
+use crate::services::GuildSettingsCache;
+use crate::utils::PermissionChecker;
+use anyhow::{anyhow, Result};
+use serenity::all::{Context, Message};
+
+const MAX_PREFIX_LEN: usize = 5;
+
+const USAGE: &str = "Usage:\n`;config` - show the current settings\n`;config prefix <prefix>`\n`;config default-langs <source> <target>`\n`;config auto-translate <on|off>`";
+
+pub struct ConfigCommand;
+
+impl ConfigCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        settings: &GuildSettingsCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+        let key = guild_id.get() as i64;
+
+        let Some(setting) = args.first().map(|arg| arg.to_lowercase()) else {
+            let current = settings.get(guild_id).await;
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!(
+                        "**Server Configuration:**\n• Prefix: `{}`\n• Default languages: {} → {}\n• Auto-translate: {}\n• LLM provider: {}\n• Flag reactions: {}\n\n{}",
+                        current.prefix,
+                        current.default_source_lang,
+                        current.default_target_lang,
+                        if current.auto_translate { "on" } else { "off" },
+                        if current.llm_enabled { "on" } else { "off" },
+                        if current.reaction_translation { "on" } else { "off" },
+                        USAGE
+                    ),
+                )
+                .await?;
+            return Ok(());
+        };
+
+        let result = match setting.as_str() {
+            "prefix" => Self::set_prefix(db, key, &args[1..]).await,
+            "default-langs" | "default-languages" => {
+                Self::set_default_languages(db, key, &args[1..]).await
+            }
+            "auto-translate" => Self::set_auto_translate(db, key, &args[1..]).await,
+            other => Err(anyhow!("Unknown setting `{}`.", other)),
+        };
+
+        let reply = match result {
+            Ok(reply) => {
+                settings.invalidate(guild_id);
+                reply
+            }
+            Err(e) => format!("❌ {}\n{}", e, USAGE),
+        };
+
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    async fn set_prefix(db: &DatabasePool, guild_id: i64, args: &[&str]) -> Result<String> {
+        let prefix = args.first().ok_or_else(|| anyhow!("Missing prefix."))?;
+
+        if prefix.chars().count() > MAX_PREFIX_LEN {
+            return Err(anyhow!(
+                "The prefix can be at most {} characters.",
+                MAX_PREFIX_LEN
+            ));
+        }
+
+        GuildRepository::get_or_create(db.pool(), guild_id).await?;
+        GuildRepository::update_prefix(db.pool(), guild_id, prefix).await?;
+
+        Ok(format!(
+            "✅ Command prefix set to `{}`, e.g. `{}help`",
+            prefix, prefix
+        ))
+    }
+
+    async fn set_default_languages(
+        db: &DatabasePool,
+        guild_id: i64,
+        args: &[&str],
+    ) -> Result<String> {
+        let [source, target] = args else {
+            return Err(anyhow!("Expected a source and a target language."));
+        };
+
+        let source = SetLogCommand::parse_language(source)?;
+        let target = SetLogCommand::parse_language(target)?;
+
+        if is_auto(&target) {
+            return Err(anyhow!("The target language cannot be `auto`."));
+        }
+
+        if same_language(&source, &target) {
+            return Err(anyhow!("Source and target languages must differ."));
+        }
+
+        GuildRepository::get_or_create(db.pool(), guild_id).await?;
+        GuildRepository::update_languages(db.pool(), guild_id, &source, &target).await?;
+
+        Ok(format!(
+            "✅ Default languages set to {} → {}",
+            source, target
+        ))
+    }
+
+    async fn set_auto_translate(db: &DatabasePool, guild_id: i64, args: &[&str]) -> Result<String> {
+        let enabled = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+            Some("on") | Some("enable") => true,
+            Some("off") | Some("disable") => false,
+            _ => return Err(anyhow!("Expected `on` or `off`.")),
+        };
+
+        GuildRepository::set_auto_translate(db.pool(), guild_id, enabled).await?;
+
+        Ok(if enabled {
+            "✅ Automatic translation enabled for configured channels.".to_string()
+        } else {
+            "✅ Automatic translation paused for this server. Channel configurations are kept."
+                .to_string()
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use crate::services::chunker::{split_message, DISCORD_MESSAGE_LIMIT};
 use serenity::all::{Context, Message};
 use anyhow::Result;
 
@@ -5,36 +6,74 @@ pub struct HelpCommand;
 
 impl HelpCommand {
     pub async fn execute(ctx: &Context, msg: &Message) -> Result<()> {
-        let help_text = r#"
+        for piece in split_message(Self::text(), DISCORD_MESSAGE_LIMIT) {
+            msg.channel_id.say(&ctx.http, piece).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn text() -> &'static str {
+        r#"
 **MegaChinese Translation Bot**
 
 **Translation Features:**
 • Automatic translation of messages in configured channels
 • Support for multiple Chinese dialects and languages
-• Multi-provider fallback (Google Translate, DeepL)
+• Multi-provider fallback (Google Translate, DeepL, LibreTranslate, MyMemory, Lingva)
 
 **Commands:**
+Most commands are also available as slash commands, e.g. `/translate` and `/set-log`.
+Right-click a message → Apps → **Translate message** for a private translation into your Discord language.
 
-`;set-log <language> <channel-id> <webhook-url>`
+`;set-log [source] [target] <channel-id> <webhook-url> [--format plain|embed|impersonate] [--mode oneway|bidirectional]`
 Setup translation logging for a channel
-Example: `;set-log chinese #translations https://discord.com/api/webhooks/...`
+Example: `;set-log zh en #translations https://discord.com/api/webhooks/... --format embed`
+Languages left out fall back to the server defaults from `;config default-langs`
+Use `auto` as the source to detect each message and skip ones already in the target language
+`embed` posts a rich embed with a jump link instead of the plain text format
+`impersonate` posts just the translation under the author's name and avatar
+`bidirectional` translates messages in the target language back into the source language too
+
+`;edit-log <channel-id> <source|target|format|mode|webhook> <value>`
+Change one setting of an existing translation channel
+Example: `;edit-log #translations mode bidirectional`
 
 `;remove-log <channel-id>`
 Remove translation logging from a channel
 
+`;add-output <channel-id> <language> <webhook-url> [format]` / `;remove-output <channel-id> <language>`
+Also translate a configured channel into another language, posted to its own webhook
+
 `;list-logs`
 List all configured translation channels in this server
 
-`;translate <source-lang> <target-lang> <text>`
-Manually translate text
+`;translate <text>` or `;translate <source-lang> <target-lang> <text>`
+Manually translate text, into your preferred language (or the server default) when no languages are given
 Example: `;translate zh en 你好世界`
 
+`;mylang <language|reset>`
+Set your preferred language for `;translate`, Translate message and 🌐 reactions
+
 `;languages`
 Show all supported languages
 
 `;stats`
 Show translation statistics for this server
 
+`;providers`
+Show the live health of each translation provider
+
+`;llm <on|off>`
+Allow the LLM translation provider for this server (off by default)
+
+`;reactions <on|off>`
+Let members react with a country flag (e.g. 🇯🇵) or 🌐 (their `;mylang` language) to get a translation (off by default)
+
+`;config [prefix|default-langs|auto-translate] [value]`
+Show or change server settings: the command prefix, default languages, and pausing automatic translation
+Commands above are shown with `;`; use your server's prefix if it was changed
+
 **Supported Languages:**
 • Chinese (Simplified): `zh`, `zh-CN`, `zh-Hans`
 • Chinese (Traditional): `zh-TW`, `zh-Hant`
@@ -60,10 +99,6 @@ Translated message here
 ```
 
 **Note:** The bot requires appropriate permissions to read messages in the configured channels.
-        "#;
-
-        msg.channel_id.say(&ctx.http, help_text).await?;
-
-        Ok(())
+        "#
     }
 }
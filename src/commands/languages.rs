@@ -0,0 +1,22 @@
+use crate::services::languages::LANGUAGES;
+use anyhow::Result;
+use serenity::all::{Context, Message};
+
+pub struct LanguagesCommand;
+
+impl LanguagesCommand {
+    pub async fn execute(ctx: &Context, msg: &Message) -> Result<()> {
+        msg.channel_id.say(&ctx.http, Self::text()).await?;
+
+        Ok(())
+    }
+
+    pub fn text() -> String {
+        let mut langs = String::from("**Supported Languages:**\n\n");
+        for language in LANGUAGES {
+            langs.push_str(&format!("• `{}` - {}\n", language.code, language.name));
+        }
+        langs.push_str("\nUse `auto` as a source language to detect it per message.");
+        langs
+    }
+}
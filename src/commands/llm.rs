@@ -0,0 +1,62 @@
+use crate::db::{DatabasePool, GuildRepository};
+use crate::services::GuildSettingsCache;
+use crate::utils::PermissionChecker;
+use anyhow::{anyhow, Result};
+use serenity::all::{Context, GuildId, Message};
+
+pub struct LlmCommand;
+
+impl LlmCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        settings: &GuildSettingsCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
+        let enabled = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+            Some("on") | Some("enable") => true,
+            Some("off") | Some("disable") => false,
+            _ => {
+                let settings =
+                    GuildRepository::get_or_create(db.pool(), guild_id.get() as i64).await?;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "Usage: `;llm <on|off>`\nLLM translation is currently **{}** for this server.",
+                            if settings.llm_enabled { "on" } else { "off" }
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        GuildRepository::set_llm_enabled(db.pool(), guild_id.get() as i64, enabled).await?;
+        settings.invalidate(guild_id);
+
+        let reply = if enabled {
+            "✅ LLM translation enabled. Messages from this server may now be sent to the configured LLM provider."
+        } else {
+            "✅ LLM translation disabled for this server."
+        };
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    /// Whether the LLM provider may be used for messages from this guild.
+    pub async fn is_allowed(settings: &GuildSettingsCache, guild_id: Option<GuildId>) -> bool {
+        match guild_id {
+            Some(guild_id) => settings.get(guild_id).await.llm_enabled,
+            None => false,
+        }
+    }
+}
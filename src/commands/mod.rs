@@ -1,7 +1,21 @@
+pub mod config;
 pub mod help;
+pub mod languages;
+pub mod llm;
+pub mod mylang;
+pub mod providers;
+pub mod reactions;
 pub mod setlog;
 pub mod stats;
+pub mod translate;
 
+pub use config::ConfigCommand;
 pub use help::HelpCommand;
+pub use languages::LanguagesCommand;
+pub use llm::LlmCommand;
+pub use mylang::MyLangCommand;
+pub use providers::ProvidersCommand;
+pub use reactions::ReactionsCommand;
 pub use setlog::SetLogCommand;
 pub use stats::StatsCommand;
+pub use translate::TranslateCommand;
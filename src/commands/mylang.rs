@@ -0,0 +1,63 @@
+use crate::commands::SetLogCommand;
+use crate::db::{DatabasePool, PreferenceRepository};
+use crate::services::detect::is_auto;
+use crate::services::languages::display_name;
+use anyhow::Result;
+use serenity::all::{Context, Message, UserId};
+
+pub struct MyLangCommand;
+
+impl MyLangCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+    ) -> Result<()> {
+        let user_id = msg.author.id.get() as i64;
+
+        let reply = match args.first() {
+            None => match PreferenceRepository::get(db.pool(), user_id).await? {
+                Some(preference) => format!(
+                    "Your preferred language is **{}** (`{}`).\nUse `;mylang <language>` to change it or `;mylang reset` to clear it.",
+                    display_name(&preference.target_language),
+                    preference.target_language
+                ),
+                None => "You have no preferred language yet.\nUsage: `;mylang <language>`, e.g. `;mylang ja`".to_string(),
+            },
+            Some(arg) if arg.eq_ignore_ascii_case("reset") || arg.eq_ignore_ascii_case("clear") => {
+                PreferenceRepository::delete(db.pool(), user_id).await?;
+                "✅ Your preferred language has been cleared.".to_string()
+            }
+            Some(arg) => match SetLogCommand::parse_language(arg) {
+                Ok(language) if is_auto(&language) => {
+                    "❌ Pick a concrete language, `auto` only makes sense as a source.".to_string()
+                }
+                Ok(language) => {
+                    PreferenceRepository::set_target_language(db.pool(), user_id, &language).await?;
+                    format!(
+                        "✅ Your preferred language is now **{}**. `;translate <text>`, the Translate message menu and 🌐 reactions will use it.",
+                        display_name(&language)
+                    )
+                }
+                Err(e) => format!("❌ {}", e),
+            },
+        };
+
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    /// The user's saved target language, if any. Lookup failures are logged
+    /// and treated as "no preference" so translations still go through.
+    pub async fn preferred_language(db: &DatabasePool, user_id: UserId) -> Option<String> {
+        match PreferenceRepository::get(db.pool(), user_id.get() as i64).await {
+            Ok(preference) => preference.map(|p| p.target_language),
+            Err(e) => {
+                tracing::warn!("Failed to load language preference for {}: {}", user_id, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::services::TranslationService;
+use anyhow::Result;
+use serenity::all::{Context, Message};
+
+pub struct ProvidersCommand;
+
+impl ProvidersCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        translation_service: &TranslationService,
+    ) -> Result<()> {
+        let snapshots = translation_service.provider_health();
+
+        if snapshots.is_empty() {
+            msg.channel_id
+                .say(&ctx.http, "No translation providers are configured.")
+                .await?;
+            return Ok(());
+        }
+
+        let mut response = String::from("**Translation Providers:**\n\n");
+
+        for (index, snapshot) in snapshots.iter().enumerate() {
+            response.push_str(&format!(
+                "{} **{}. {}** — {}\n",
+                snapshot.state.emoji(),
+                index + 1,
+                snapshot.name,
+                snapshot.state.label()
+            ));
+
+            response.push_str(&format!(
+                "• Successes: {} | Failures: {} (consecutive: {})\n",
+                snapshot.total_successes, snapshot.total_failures, snapshot.consecutive_failures
+            ));
+
+            if let Some(retry_in) = snapshot.retry_in {
+                response.push_str(&format!("• Re-probe in: {}s\n", retry_in.as_secs()));
+            }
+
+            if let Some(last_success) = snapshot.last_success_at {
+                response.push_str(&format!(
+                    "• Last success: <t:{}:R>\n",
+                    last_success.timestamp()
+                ));
+            }
+
+            if let (Some(error), Some(failed_at)) = (&snapshot.last_error, snapshot.last_failure_at)
+            {
+                let error: String = error.chars().take(150).collect();
+                response.push_str(&format!(
+                    "• Last error (<t:{}:R>): `{}`\n",
+                    failed_at.timestamp(),
+                    error.replace('`', "'")
+                ));
+            }
+
+            response.push('\n');
+        }
+
+        msg.channel_id.say(&ctx.http, response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use crate::db::{DatabasePool, GuildRepository};
+use crate::services::GuildSettingsCache;
+use crate::utils::PermissionChecker;
+use anyhow::{anyhow, Result};
+use serenity::all::{Context, Message};
+
+pub struct ReactionsCommand;
+
+impl ReactionsCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        settings: &GuildSettingsCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
+        let enabled = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+            Some("on") | Some("enable") => true,
+            Some("off") | Some("disable") => false,
+            _ => {
+                let settings =
+                    GuildRepository::get_or_create(db.pool(), guild_id.get() as i64).await?;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "Usage: `;reactions <on|off>`\nFlag reaction translation is currently **{}** for this server.",
+                            if settings.reaction_translation { "on" } else { "off" }
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        GuildRepository::set_reaction_translation(db.pool(), guild_id.get() as i64, enabled)
+            .await?;
+        settings.invalidate(guild_id);
+
+        let reply = if enabled {
+            "✅ Flag reaction translation enabled. React to a message with a country flag (e.g. 🇯🇵) to translate it."
+        } else {
+            "✅ Flag reaction translation disabled for this server."
+        };
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+}
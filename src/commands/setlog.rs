@@ -1,8 +1,17 @@
-use crate::db::{ChannelRepository, DatabasePool};
-use crate::models::TranslationChannel;
+use crate::db::{ChannelRepository, DatabasePool, OutputRepository};
+use crate::models::{ChannelMode, GuildSettings, OutputFormat, TranslationChannel, TranslationOutput};
+use crate::services::detect::{is_auto, same_language};
+use crate::services::languages;
+use crate::services::{ChannelConfigCache, GuildSettingsCache};
 use crate::utils::PermissionChecker;
 use anyhow::{anyhow, Result};
-use serenity::all::{Context, Message};
+use serenity::all::{ChannelId, Context, Message};
+
+const SET_LOG_USAGE: &str = "Usage: `;set-log [source] [target] <#channel> <webhook-url> [--format plain|embed|impersonate] [--mode oneway|bidirectional]`\nExample: `;set-log zh en #translations https://discord.com/api/webhooks/... --format embed`";
+
+const EDIT_LOG_USAGE: &str = "Usage: `;edit-log <#channel> <source|target|format|mode|webhook> <value>`\nExample: `;edit-log #translations format embed`";
+
+const FOREIGN_CHANNEL: &str = "❌ That channel belongs to another server.";
 
 pub struct SetLogCommand;
 
@@ -12,27 +21,396 @@ impl SetLogCommand {
         msg: &Message,
         args: Vec<&str>,
         db: &DatabasePool,
+        channels: &ChannelConfigCache,
+        settings: &GuildSettingsCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
+        let defaults = settings.get(guild_id).await;
+
+        let translation_channel = match Self::parse_set_log(&defaults, &args) {
+            Ok(channel) => channel,
+            Err(e) => {
+                msg.channel_id
+                    .say(&ctx.http, format!("❌ {}\n{}", e, SET_LOG_USAGE))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let reply = Self::save(ctx, db, channels, &translation_channel).await?;
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    /// Stores a validated channel configuration and returns the confirmation
+    /// shown to the admin.
+    pub async fn save(
+        ctx: &Context,
+        db: &DatabasePool,
+        channels: &ChannelConfigCache,
+        channel: &TranslationChannel,
+    ) -> Result<String> {
+        if !Self::in_guild(ctx, channel.guild_id, channel.channel_id).await {
+            return Ok(FOREIGN_CHANNEL.to_string());
+        }
+
+        let Some(saved) = ChannelRepository::create(db.pool(), channel).await? else {
+            return Ok(FOREIGN_CHANNEL.to_string());
+        };
+        channels.refresh(saved.channel_id).await;
+
+        Ok(format!(
+            "✅ Translation logging configured!\nChannel: <#{}>\n{}\nWebhook: Set",
+            saved.channel_id,
+            Self::describe(&saved)
+        ))
+    }
+
+    /// Whether `channel_id` is a channel of `guild_id`. Channels the bot
+    /// cannot see count as foreign, so another server's channel can never be
+    /// configured.
+    async fn in_guild(ctx: &Context, guild_id: i64, channel_id: i64) -> bool {
+        if channel_id <= 0 {
+            return false;
+        }
+
+        match ChannelId::new(channel_id as u64).to_channel(ctx).await {
+            Ok(channel) => channel
+                .guild()
+                .is_some_and(|channel| channel.guild_id.get() as i64 == guild_id),
+            Err(e) => {
+                tracing::warn!("Failed to fetch channel {}: {}", channel_id, e);
+                false
+            }
+        }
+    }
+
+    pub async fn edit_log(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        channels: &ChannelConfigCache,
     ) -> Result<()> {
         PermissionChecker::require_admin(ctx, msg).await?;
 
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
         if args.len() < 3 {
+            msg.channel_id.say(&ctx.http, EDIT_LOG_USAGE).await?;
+            return Ok(());
+        }
+
+        let channel_id = Self::parse_channel_id(args[0])?;
+
+        let Some(existing) =
+            ChannelRepository::get_in_guild(db.pool(), guild_id.get() as i64, channel_id).await?
+        else {
             msg.channel_id
-                .say(
-                    &ctx.http,
-                    "Usage: `;set-log <language> <channel-id> <webhook-url>`\nExample: `;set-log chinese #translations https://discord.com/api/webhooks/...`",
-                )
+                .say(&ctx.http, "❌ No translation logging found for that channel")
                 .await?;
             return Ok(());
+        };
+
+        let updated = match Self::apply_edit(existing, args[1], args[2]) {
+            Ok(channel) => channel,
+            Err(e) => {
+                msg.channel_id
+                    .say(&ctx.http, format!("❌ {}\n{}", e, EDIT_LOG_USAGE))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let Some(saved) = ChannelRepository::create(db.pool(), &updated).await? else {
+            msg.channel_id.say(&ctx.http, FOREIGN_CHANNEL).await?;
+            return Ok(());
+        };
+        channels.refresh(saved.channel_id).await;
+
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "✅ Updated translation logging for <#{}>\n{}",
+                    saved.channel_id,
+                    Self::describe(&saved)
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Parses `[source] [target] <channel> <webhook> [options]`. Languages
+    /// left out fall back to the guild's default languages.
+    fn parse_set_log(defaults: &GuildSettings, args: &[&str]) -> Result<TranslationChannel> {
+        let is_channel = |arg: &str| Self::parse_channel_id(arg).is_ok();
+
+        let (source, target, rest) = match args {
+            [channel, ..] if is_channel(channel) => (
+                defaults.default_source_lang.as_str(),
+                defaults.default_target_lang.as_str(),
+                args,
+            ),
+            [source, channel, ..] if is_channel(channel) => {
+                (*source, defaults.default_target_lang.as_str(), &args[1..])
+            }
+            [source, target, rest @ ..] => (*source, *target, rest),
+            _ => return Err(anyhow!("Missing arguments.")),
+        };
+
+        let [channel, webhook_url, options @ ..] = rest else {
+            return Err(anyhow!("Missing channel or webhook URL."));
+        };
+
+        let channel_id = Self::parse_channel_id(channel)?;
+        let (output_format, mode) = Self::parse_options(options)?;
+
+        Self::build_channel(
+            defaults.guild_id,
+            channel_id,
+            source,
+            target,
+            webhook_url,
+            output_format.unwrap_or(OutputFormat::Plain),
+            mode.unwrap_or(ChannelMode::OneWay),
+        )
+    }
+
+    /// Validates raw user input into a channel configuration. Shared by the
+    /// prefix and slash versions of set-log.
+    pub fn build_channel(
+        guild_id: i64,
+        channel_id: i64,
+        source: &str,
+        target: &str,
+        webhook_url: &str,
+        output_format: OutputFormat,
+        mode: ChannelMode,
+    ) -> Result<TranslationChannel> {
+        let source_lang = Self::parse_language(source)?;
+        let target_lang = Self::parse_language(target)?;
+
+        if !Self::validate_webhook_url(webhook_url) {
+            return Err(anyhow!(
+                "Invalid webhook URL. Please provide a valid Discord webhook URL."
+            ));
+        }
+
+        let channel = TranslationChannel::new(
+            guild_id,
+            channel_id,
+            webhook_url.to_string(),
+            source_lang,
+            target_lang,
+        )
+        .with_output_format(output_format)
+        .with_mode(mode);
+
+        Self::validate_channel(&channel)?;
+
+        Ok(channel)
+    }
+
+    /// Accepts `--format <value>`, `--mode <value>`, their `--key=value`
+    /// spellings, and bare values such as `embed` or `bidirectional`.
+    fn parse_options(options: &[&str]) -> Result<(Option<OutputFormat>, Option<ChannelMode>)> {
+        let mut format = None;
+        let mut mode = None;
+        let mut iter = options.iter();
+
+        while let Some(option) = iter.next() {
+            let (key, value) = match option.strip_prefix("--") {
+                Some(flag) => match flag.split_once('=') {
+                    Some((key, value)) => (Some(key), value),
+                    None => (
+                        Some(flag),
+                        *iter
+                            .next()
+                            .ok_or_else(|| anyhow!("Option `{}` needs a value.", option))?,
+                    ),
+                },
+                None => (None, *option),
+            };
+
+            match key {
+                Some("format") => {
+                    format = Some(
+                        OutputFormat::parse(value)
+                            .ok_or_else(|| anyhow!("Unknown format `{}`.", value))?,
+                    );
+                }
+                Some("mode") => {
+                    mode = Some(
+                        ChannelMode::parse(value)
+                            .ok_or_else(|| anyhow!("Unknown mode `{}`.", value))?,
+                    );
+                }
+                Some(other) => return Err(anyhow!("Unknown option `--{}`.", other)),
+                None => {
+                    if let Some(parsed) = OutputFormat::parse(value) {
+                        format = Some(parsed);
+                    } else if let Some(parsed) = ChannelMode::parse(value) {
+                        mode = Some(parsed);
+                    } else {
+                        return Err(anyhow!("Unknown option `{}`.", value));
+                    }
+                }
+            }
+        }
+
+        Ok((format, mode))
+    }
+
+    fn apply_edit(
+        mut channel: TranslationChannel,
+        property: &str,
+        value: &str,
+    ) -> Result<TranslationChannel> {
+        match property.to_lowercase().as_str() {
+            "source" => channel.source_language = Self::parse_language(value)?,
+            "target" => channel.target_language = Self::parse_language(value)?,
+            "format" => {
+                let format = OutputFormat::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown format `{}`.", value))?;
+                channel = channel.with_output_format(format);
+            }
+            "mode" => {
+                let mode =
+                    ChannelMode::parse(value).ok_or_else(|| anyhow!("Unknown mode `{}`.", value))?;
+                channel = channel.with_mode(mode);
+            }
+            "webhook" => {
+                if !Self::validate_webhook_url(value) {
+                    return Err(anyhow!(
+                        "Invalid webhook URL. Please provide a valid Discord webhook URL."
+                    ));
+                }
+                channel.webhook_url = value.to_string();
+            }
+            other => return Err(anyhow!("Unknown property `{}`.", other)),
+        }
+
+        Self::validate_channel(&channel)?;
+
+        Ok(channel)
+    }
+
+    fn validate_channel(channel: &TranslationChannel) -> Result<()> {
+        if is_auto(&channel.target_language) {
+            return Err(anyhow!("The target language cannot be `auto`."));
         }
 
+        if same_language(&channel.source_language, &channel.target_language) {
+            return Err(anyhow!("Source and target languages must differ."));
+        }
+
+        if channel.mode() == ChannelMode::Bidirectional && is_auto(&channel.source_language) {
+            return Err(anyhow!(
+                "Bidirectional channels need a concrete source language, not `auto`."
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn describe(channel: &TranslationChannel) -> String {
+        format!(
+            "Language: {} {} {}\nFormat: {}",
+            channel.source_language,
+            channel.mode().arrow(),
+            channel.target_language,
+            channel.output_format().as_str()
+        )
+    }
+
+    pub async fn remove_log(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        channels: &ChannelConfigCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
         let guild_id = msg
             .guild_id
             .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
 
-        let source_lang = Self::parse_language(args[0])?;
-        let channel_id = Self::parse_channel_id(args[1])?;
+        if args.is_empty() {
+            msg.channel_id
+                .say(&ctx.http, "Usage: `;remove-log <channel-id>`")
+                .await?;
+            return Ok(());
+        }
+
+        let channel_id = Self::parse_channel_id(args[0])?;
+
+        let reply = Self::remove(db, channels, guild_id.get() as i64, channel_id).await?;
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        db: &DatabasePool,
+        channels: &ChannelConfigCache,
+        guild_id: i64,
+        channel_id: i64,
+    ) -> Result<String> {
+        let deleted = ChannelRepository::delete(db.pool(), guild_id, channel_id).await?;
+        channels.refresh(channel_id).await;
+
+        if deleted {
+            Ok(format!("✅ Translation logging removed for <#{}>", channel_id))
+        } else {
+            Ok("❌ No translation logging found for that channel".to_string())
+        }
+    }
+
+    pub async fn add_output(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        channels: &ChannelConfigCache,
+    ) -> Result<()> {
+        PermissionChecker::require_admin(ctx, msg).await?;
+
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
+        if args.len() < 3 {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    "Usage: `;add-output <channel-id> <language> <webhook-url> [plain|embed|impersonate]`\nExample: `;add-output #announcements ja https://discord.com/api/webhooks/...`",
+                )
+                .await?;
+            return Ok(());
+        }
+
+        let channel_id = Self::parse_channel_id(args[0])?;
+        let target_lang = Self::parse_language(args[1])?;
         let webhook_url = args[2];
 
+        if target_lang == "auto" {
+            msg.channel_id
+                .say(&ctx.http, "Output languages must be concrete, `auto` is only valid as a source.")
+                .await?;
+            return Ok(());
+        }
+
         if !Self::validate_webhook_url(webhook_url) {
             msg.channel_id
                 .say(&ctx.http, "Invalid webhook URL. Please provide a valid Discord webhook URL.")
@@ -40,22 +418,46 @@ impl SetLogCommand {
             return Ok(());
         }
 
-        let translation_channel = TranslationChannel::new(
-            guild_id.get() as i64,
-            channel_id,
-            webhook_url.to_string(),
-            source_lang.clone(),
-            "en".to_string(),
-        );
+        let mut output_format = OutputFormat::Plain;
+        if let Some(option) = args.get(3) {
+            match OutputFormat::parse(option) {
+                Some(format) => output_format = format,
+                None => {
+                    msg.channel_id
+                        .say(
+                            &ctx.http,
+                            format!("Unknown option `{}`. Expected `plain`, `embed` or `impersonate`.", option),
+                        )
+                        .await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        if ChannelRepository::get_in_guild(db.pool(), guild_id.get() as i64, channel_id)
+            .await?
+            .is_none()
+        {
+            msg.channel_id
+                .say(&ctx.http, "❌ That channel is not configured yet. Use `;set-log` first.")
+                .await?;
+            return Ok(());
+        }
 
-        ChannelRepository::create(db.pool(), &translation_channel).await?;
+        let output = TranslationOutput::new(channel_id, target_lang.clone(), webhook_url.to_string())
+            .with_output_format(output_format);
+
+        OutputRepository::upsert(db.pool(), &output).await?;
+        channels.refresh(channel_id).await;
 
         msg.channel_id
             .say(
                 &ctx.http,
                 format!(
-                    "✅ Translation logging configured!\nChannel: <#{}>\nLanguage: {} → en\nWebhook: Set",
-                    channel_id, source_lang
+                    "✅ Added output for <#{}>\nLanguage: {}\nFormat: {}",
+                    channel_id,
+                    target_lang,
+                    output_format.as_str()
                 ),
             )
             .await?;
@@ -63,35 +465,44 @@ impl SetLogCommand {
         Ok(())
     }
 
-    pub async fn remove_log(
+    pub async fn remove_output(
         ctx: &Context,
         msg: &Message,
         args: Vec<&str>,
         db: &DatabasePool,
+        channels: &ChannelConfigCache,
     ) -> Result<()> {
         PermissionChecker::require_admin(ctx, msg).await?;
 
-        if args.is_empty() {
+        let guild_id = msg
+            .guild_id
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
+
+        if args.len() < 2 {
             msg.channel_id
-                .say(&ctx.http, "Usage: `;remove-log <channel-id>`")
+                .say(&ctx.http, "Usage: `;remove-output <channel-id> <language>`")
                 .await?;
             return Ok(());
         }
 
         let channel_id = Self::parse_channel_id(args[0])?;
+        let target_lang = Self::parse_language(args[1])?;
 
-        let deleted = ChannelRepository::delete(db.pool(), channel_id).await?;
+        let deleted =
+            OutputRepository::delete(db.pool(), guild_id.get() as i64, channel_id, &target_lang)
+                .await?;
+        channels.refresh(channel_id).await;
 
         if deleted {
             msg.channel_id
                 .say(
                     &ctx.http,
-                    format!("✅ Translation logging removed for <#{}>", channel_id),
+                    format!("✅ Removed {} output for <#{}>", target_lang, channel_id),
                 )
                 .await?;
         } else {
             msg.channel_id
-                .say(&ctx.http, "❌ No translation logging found for that channel")
+                .say(&ctx.http, "❌ No such output for that channel")
                 .await?;
         }
 
@@ -105,44 +516,50 @@ impl SetLogCommand {
             .guild_id
             .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
 
-        let channels = ChannelRepository::get_by_guild(db.pool(), guild_id.get() as i64).await?;
+        let reply = Self::list(db, guild_id.get() as i64).await?;
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        Ok(())
+    }
+
+    pub async fn list(db: &DatabasePool, guild_id: i64) -> Result<String> {
+        let channels = ChannelRepository::get_by_guild(db.pool(), guild_id).await?;
 
         if channels.is_empty() {
-            msg.channel_id
-                .say(&ctx.http, "No translation channels configured for this server.")
-                .await?;
-            return Ok(());
+            return Ok("No translation channels configured for this server.".to_string());
         }
 
         let mut response = String::from("**Configured Translation Channels:**\n\n");
         for channel in channels {
             response.push_str(&format!(
-                "• <#{}> - {} → {}\n",
-                channel.channel_id, channel.source_language, channel.target_language
+                "• <#{}> - {} {} {} ({})\n",
+                channel.channel_id,
+                channel.source_language,
+                channel.mode().arrow(),
+                channel.target_language,
+                channel.output_format().as_str()
             ));
-        }
 
-        msg.channel_id.say(&ctx.http, response).await?;
+            for output in OutputRepository::get_by_channel(db.pool(), channel.channel_id).await? {
+                response.push_str(&format!(
+                    "  ↳ {} ({})\n",
+                    output.target_language,
+                    output.output_format().as_str()
+                ));
+            }
+        }
 
-        Ok(())
+        Ok(response)
     }
 
-    fn parse_language(lang: &str) -> Result<String> {
-        let lang_lower = lang.to_lowercase();
-        let normalized = match lang_lower.as_str() {
-            "chinese" | "zh" | "cn" => "zh",
-            "english" | "en" => "en",
-            "japanese" | "ja" | "jp" => "ja",
-            "korean" | "ko" | "kr" => "ko",
-            "german" | "de" => "de",
-            "french" | "fr" => "fr",
-            "spanish" | "es" => "es",
-            "italian" | "it" => "it",
-            "portuguese" | "pt" => "pt",
-            "russian" | "ru" => "ru",
-            other => other,
-        };
-        Ok(normalized.to_string())
+    pub fn parse_language(lang: &str) -> Result<String> {
+        if is_auto(lang) || lang.eq_ignore_ascii_case("detect") {
+            return Ok("auto".to_string());
+        }
+
+        languages::lookup(lang)
+            .map(|language| language.code.to_string())
+            .ok_or_else(|| anyhow!("Unknown language `{}`. See `;languages` for the supported list.", lang))
     }
 
     fn parse_channel_id(input: &str) -> Result<i64> {
@@ -1,11 +1,18 @@
-use crate::db::DatabasePool;
+use crate::db::{DatabasePool, HistoryRepository};
+use crate::services::filter::SkipReason;
+use crate::services::TranslationService;
 use anyhow::Result;
 use serenity::all::{Context, Message};
 
 pub struct StatsCommand;
 
 impl StatsCommand {
-    pub async fn execute(ctx: &Context, msg: &Message, db: &DatabasePool) -> Result<()> {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        db: &DatabasePool,
+        translation_service: &TranslationService,
+    ) -> Result<()> {
         let guild_id = msg.guild_id.map(|g| g.get() as i64);
 
         if guild_id.is_none() {
@@ -15,7 +22,17 @@ impl StatsCommand {
             return Ok(());
         }
 
-        let guild_id = guild_id.unwrap();
+        let report = Self::report(db, guild_id.unwrap(), translation_service).await?;
+        msg.channel_id.say(&ctx.http, report).await?;
+
+        Ok(())
+    }
+
+    pub async fn report(
+        db: &DatabasePool,
+        guild_id: i64,
+        translation_service: &TranslationService,
+    ) -> Result<String> {
 
         let channel_count: i64 = sqlx::query_scalar(
             "SELECT COUNT(*) FROM translation_channels WHERE guild_id = $1 AND is_active = true",
@@ -38,6 +55,70 @@ impl StatsCommand {
         .fetch_one(db.pool())
         .await?;
 
+        let skip_counts = HistoryRepository::skip_counts(db.pool(), guild_id).await?;
+        let skipped = if skip_counts.is_empty() {
+            "• None".to_string()
+        } else {
+            skip_counts
+                .iter()
+                .map(|(reason, count)| format!("• {}: {}", SkipReason::label(reason), count))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let cache_stats = match translation_service.cache_stats() {
+            Some(stats) => format!(
+                "• Hits: {} ({} memory, {} database)\n• Misses: {}\n• Hit rate: {:.1}%\n• Cached entries: {}",
+                stats.hits(),
+                stats.memory_hits,
+                stats.persistent_hits,
+                stats.misses,
+                stats.hit_rate(),
+                stats.entries
+            ),
+            None => "• Disabled".to_string(),
+        };
+
+        let provider_names = translation_service.provider_names();
+        let providers = if provider_names.is_empty() {
+            "• None configured".to_string()
+        } else {
+            provider_names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    if index == 0 {
+                        format!("• Primary: {}", name)
+                    } else {
+                        format!("• Fallback {}: {}", index, name)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let quotas = translation_service
+            .provider_quotas()
+            .iter()
+            .map(|quota| {
+                let remaining = match (quota.remaining(), quota.daily_char_quota) {
+                    (Some(remaining), Some(limit)) => {
+                        format!("{} / {} characters left today", remaining, limit)
+                    }
+                    _ => format!("no daily quota ({} characters today)", quota.used_today),
+                };
+                match quota.requests_per_minute {
+                    Some(rate) => format!("• {}: {}, {} requests/min", quota.name, remaining, rate),
+                    None => format!("• {}: {}", quota.name, remaining),
+                }
+            })
+            .collect::<Vec<_>>();
+        let quotas = if quotas.is_empty() {
+            "• None configured".to_string()
+        } else {
+            quotas.join("\n")
+        };
+
         let stats_message = format!(
             r#"**Translation Statistics**
 
@@ -46,17 +127,28 @@ impl StatsCommand {
 • Total translations: {}
 • Translations (24h): {}
 
+⏭️ **Skipped Messages:**
+{}
+
 **Translation Providers:**
-• Primary: LibreTranslate (Free & Open Source)
-• Fallback 1: MyMemory
-• Fallback 2: Lingva
+{}
+
+📏 **Provider Quotas:**
+{}
+
+🗃️ **Translation Cache:**
+{}
 
 Use `;list-logs` to see configured channels."#,
-            channel_count, translation_count, recent_translations
+            channel_count,
+            translation_count,
+            recent_translations,
+            skipped,
+            providers,
+            quotas,
+            cache_stats
         );
 
-        msg.channel_id.say(&ctx.http, stats_message).await?;
-
-        Ok(())
+        Ok(stats_message)
     }
 }
@@ -0,0 +1,172 @@
+use crate::commands::{LlmCommand, MyLangCommand};
+use crate::db::DatabasePool;
+use crate::services::chunker::{split_message, DISCORD_MESSAGE_LIMIT};
+use crate::services::detect::{is_auto, same_language};
+use crate::services::filter::{self, SkipReason};
+use crate::services::languages::{self, display_name};
+use crate::services::{GuildSettingsCache, TranslationService};
+use anyhow::Result;
+use serenity::all::{Context, GuildId, Message};
+
+const USAGE: &str = "Usage: `;translate <text>` (into your `;mylang` or the server's default language) or `;translate <source-lang> <target-lang> <text>`\nExample: `;translate zh en 你好世界`";
+
+pub struct TranslateCommand;
+
+impl TranslateCommand {
+    pub async fn execute(
+        ctx: &Context,
+        msg: &Message,
+        args: Vec<&str>,
+        db: &DatabasePool,
+        settings: &GuildSettingsCache,
+        translation_service: &TranslationService,
+    ) -> Result<()> {
+        if args.is_empty() {
+            msg.channel_id.say(&ctx.http, USAGE).await?;
+            return Ok(());
+        }
+
+        let explicit = args.len() >= 3 && Self::is_language(args[0]) && Self::is_language(args[1]);
+
+        let (source_lang, target_lang, text) = if explicit {
+            (
+                args[0].to_string(),
+                args[1].to_string(),
+                args[2..].join(" "),
+            )
+        } else {
+            let preferred = MyLangCommand::preferred_language(db, msg.author.id).await;
+            let guild_default = match msg.guild_id {
+                Some(guild_id) => Some(settings.get(guild_id).await.default_target_lang),
+                None => None,
+            };
+
+            let Some(target_lang) = preferred.or(guild_default) else {
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "Set a preferred language with `;mylang <language>` first.\n{}",
+                            USAGE
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            };
+            ("auto".to_string(), target_lang, args.join(" "))
+        };
+
+        let typing = msg.channel_id.start_typing(&ctx.http);
+
+        let reply = Self::run(
+            settings,
+            translation_service,
+            msg.guild_id,
+            &text,
+            &source_lang,
+            &target_lang,
+        )
+        .await;
+
+        for piece in split_message(&reply, DISCORD_MESSAGE_LIMIT) {
+            msg.channel_id.say(&ctx.http, piece).await?;
+        }
+
+        typing.stop();
+
+        Ok(())
+    }
+
+    fn is_language(value: &str) -> bool {
+        is_auto(value) || languages::lookup(value).is_some()
+    }
+
+    /// Translates `text` and formats the reply, or the failure, for display.
+    pub async fn run(
+        settings: &GuildSettingsCache,
+        translation_service: &TranslationService,
+        guild_id: Option<GuildId>,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> String {
+        let allow_llm = LlmCommand::is_allowed(settings, guild_id).await;
+
+        match translation_service
+            .translate_with_fallback(text, source_lang, target_lang, allow_llm)
+            .await
+        {
+            Ok(translated) => format!(
+                "**Translation ({} → {}):**\n{}",
+                source_lang, target_lang, translated
+            ),
+            Err(e) => format!("❌ Translation failed: {}", e),
+        }
+    }
+
+    /// Private translation of an existing message for the "Translate message"
+    /// context menu, including what was detected and which provider answered.
+    pub async fn translate_message(
+        settings: &GuildSettingsCache,
+        translation_service: &TranslationService,
+        guild_id: Option<GuildId>,
+        content: &str,
+        target_lang: &str,
+    ) -> String {
+        Self::message_translation(settings, translation_service, guild_id, content, target_lang)
+            .await
+            .unwrap_or_else(|e| format!("❌ Translation failed: {}", e))
+    }
+
+    /// Like `translate_message`, but hands provider failures back to the
+    /// caller instead of formatting them into the reply.
+    pub async fn message_translation(
+        settings: &GuildSettingsCache,
+        translation_service: &TranslationService,
+        guild_id: Option<GuildId>,
+        content: &str,
+        target_lang: &str,
+    ) -> Result<String> {
+        if let Some(reason) = filter::classify(content) {
+            return Ok(format!(
+                "Nothing to translate: {}.",
+                SkipReason::label(reason.as_str()).to_lowercase()
+            ));
+        }
+
+        let detection = translation_service.detect_language(content).await;
+
+        if let Some(detection) = &detection {
+            if same_language(&detection.language, target_lang) {
+                return Ok(format!(
+                    "This message is already in {}.",
+                    display_name(&detection.language)
+                ));
+            }
+        }
+
+        let source_lang = detection
+            .as_ref()
+            .map(|detection| detection.language.as_str())
+            .unwrap_or("auto");
+        let allow_llm = LlmCommand::is_allowed(settings, guild_id).await;
+
+        let response = translation_service
+            .translate_text(content, source_lang, target_lang, allow_llm)
+            .await?;
+
+        let detected = detection
+            .map(|detection| detection.language)
+            .or(response.detected_language)
+            .map(|language| display_name(&language))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        Ok(format!(
+            "**Translation → {}:**\n{}\n\n-# Detected: {} • Provider: {}",
+            display_name(target_lang),
+            response.translated_text,
+            detected,
+            response.provider.as_deref().unwrap_or("unknown")
+        ))
+    }
+}
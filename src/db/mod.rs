@@ -2,4 +2,7 @@ pub mod pool;
 pub mod repository;
 
 pub use pool::DatabasePool;
-pub use repository::{ChannelRepository, HistoryRepository};
+pub use repository::{
+    CacheRepository, ChannelRepository, GuildRepository, HistoryRepository, JobRepository,
+    OutputRepository, PreferenceRepository, ReactionRepository, UsageRepository,
+};
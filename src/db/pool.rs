@@ -28,4 +28,9 @@ impl DatabasePool {
         sqlx::query("SELECT 1").execute(&self.pool).await?;
         Ok(())
     }
+
+    /// Waits for checked-out connections to be returned, then closes them all.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
 }
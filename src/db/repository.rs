@@ -1,22 +1,35 @@
-use crate::models::{GuildSettings, TranslationChannel, TranslationHistory};
+use crate::models::{
+    CachedTranslation, GuildSettings, TranslationChannel, TranslationHistory, TranslationJob,
+    TranslationOutput, UserPreference,
+};
 use anyhow::Result;
+use chrono::NaiveDate;
 use sqlx::PgPool;
 
 pub struct ChannelRepository;
 
 impl ChannelRepository {
-    pub async fn create(pool: &PgPool, channel: &TranslationChannel) -> Result<TranslationChannel> {
+    /// Creates or updates a channel's configuration. Returns `None` without
+    /// touching anything when the channel belongs to another guild.
+    pub async fn create(
+        pool: &PgPool,
+        channel: &TranslationChannel,
+    ) -> Result<Option<TranslationChannel>> {
         let result = sqlx::query_as::<_, TranslationChannel>(
             r#"
             INSERT INTO translation_channels
-            (guild_id, channel_id, webhook_url, source_language, target_language, is_active)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            (guild_id, channel_id, webhook_url, source_language, target_language, is_active, output_format, mode)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             ON CONFLICT (channel_id)
             DO UPDATE SET
                 webhook_url = EXCLUDED.webhook_url,
                 source_language = EXCLUDED.source_language,
                 target_language = EXCLUDED.target_language,
+                is_active = EXCLUDED.is_active,
+                output_format = EXCLUDED.output_format,
+                mode = EXCLUDED.mode,
                 updated_at = NOW()
+            WHERE translation_channels.guild_id = EXCLUDED.guild_id
             RETURNING *
             "#,
         )
@@ -26,7 +39,9 @@ impl ChannelRepository {
         .bind(&channel.source_language)
         .bind(&channel.target_language)
         .bind(channel.is_active)
-        .fetch_one(pool)
+        .bind(&channel.output_format)
+        .bind(&channel.mode)
+        .fetch_optional(pool)
         .await?;
 
         Ok(result)
@@ -43,6 +58,33 @@ impl ChannelRepository {
         Ok(result)
     }
 
+    /// Like `get_by_channel_id`, but only finds channels owned by the guild.
+    pub async fn get_in_guild(
+        pool: &PgPool,
+        guild_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<TranslationChannel>> {
+        let result = sqlx::query_as::<_, TranslationChannel>(
+            "SELECT * FROM translation_channels WHERE guild_id = $1 AND channel_id = $2 AND is_active = true",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn get_all_active(pool: &PgPool) -> Result<Vec<TranslationChannel>> {
+        let results = sqlx::query_as::<_, TranslationChannel>(
+            "SELECT * FROM translation_channels WHERE is_active = true",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(results)
+    }
+
     pub async fn get_by_guild(pool: &PgPool, guild_id: i64) -> Result<Vec<TranslationChannel>> {
         let results = sqlx::query_as::<_, TranslationChannel>(
             "SELECT * FROM translation_channels WHERE guild_id = $1 AND is_active = true ORDER BY created_at DESC",
@@ -54,11 +96,104 @@ impl ChannelRepository {
         Ok(results)
     }
 
-    pub async fn delete(pool: &PgPool, channel_id: i64) -> Result<bool> {
+    /// Deactivates a channel and drops its extra outputs, so configuring it
+    /// again starts from just the primary target.
+    pub async fn delete(pool: &PgPool, guild_id: i64, channel_id: i64) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE translation_channels SET is_active = false, updated_at = NOW() WHERE guild_id = $1 AND channel_id = $2",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            sqlx::query("DELETE FROM translation_outputs WHERE channel_id = $1")
+                .bind(channel_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+pub struct OutputRepository;
+
+impl OutputRepository {
+    pub async fn upsert(pool: &PgPool, output: &TranslationOutput) -> Result<TranslationOutput> {
+        let result = sqlx::query_as::<_, TranslationOutput>(
+            r#"
+            INSERT INTO translation_outputs (channel_id, target_language, webhook_url, output_format)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (channel_id, target_language)
+            DO UPDATE SET
+                webhook_url = EXCLUDED.webhook_url,
+                output_format = EXCLUDED.output_format
+            RETURNING *
+            "#,
+        )
+        .bind(output.channel_id)
+        .bind(&output.target_language)
+        .bind(&output.webhook_url)
+        .bind(&output.output_format)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn get_by_channel(pool: &PgPool, channel_id: i64) -> Result<Vec<TranslationOutput>> {
+        let result = sqlx::query_as::<_, TranslationOutput>(
+            "SELECT * FROM translation_outputs WHERE channel_id = $1 ORDER BY target_language",
+        )
+        .bind(channel_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Outputs of every active channel.
+    pub async fn get_all_active(pool: &PgPool) -> Result<Vec<TranslationOutput>> {
+        let result = sqlx::query_as::<_, TranslationOutput>(
+            r#"
+            SELECT o.* FROM translation_outputs o
+            JOIN translation_channels c ON c.channel_id = o.channel_id
+            WHERE c.is_active = true
+            ORDER BY o.channel_id, o.target_language
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Removes an output of a channel owned by the guild.
+    pub async fn delete(
+        pool: &PgPool,
+        guild_id: i64,
+        channel_id: i64,
+        target_language: &str,
+    ) -> Result<bool> {
         let result = sqlx::query(
-            "UPDATE translation_channels SET is_active = false, updated_at = NOW() WHERE channel_id = $1",
+            r#"
+            DELETE FROM translation_outputs o
+            USING translation_channels c
+            WHERE c.channel_id = o.channel_id
+              AND c.guild_id = $1
+              AND o.channel_id = $2
+              AND o.target_language = $3
+            "#,
         )
+        .bind(guild_id)
         .bind(channel_id)
+        .bind(target_language)
         .execute(pool)
         .await?;
 
@@ -68,7 +203,6 @@ impl ChannelRepository {
 
 pub struct GuildRepository;
 
-#[allow(dead_code)]
 impl GuildRepository {
     pub async fn get_or_create(pool: &PgPool, guild_id: i64) -> Result<GuildSettings> {
         let result = sqlx::query_as::<_, GuildSettings>(
@@ -86,6 +220,17 @@ impl GuildRepository {
         Ok(result)
     }
 
+    pub async fn get(pool: &PgPool, guild_id: i64) -> Result<Option<GuildSettings>> {
+        let result = sqlx::query_as::<_, GuildSettings>(
+            "SELECT * FROM guild_settings WHERE guild_id = $1",
+        )
+        .bind(guild_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
     pub async fn update_prefix(pool: &PgPool, guild_id: i64, prefix: &str) -> Result<()> {
         sqlx::query("UPDATE guild_settings SET prefix = $1, updated_at = NOW() WHERE guild_id = $2")
             .bind(prefix)
@@ -96,6 +241,54 @@ impl GuildRepository {
         Ok(())
     }
 
+    pub async fn set_llm_enabled(pool: &PgPool, guild_id: i64, enabled: bool) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_settings (guild_id, llm_enabled)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE SET llm_enabled = EXCLUDED.llm_enabled, updated_at = NOW()
+            "#,
+        )
+        .bind(guild_id)
+        .bind(enabled)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_auto_translate(pool: &PgPool, guild_id: i64, enabled: bool) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_settings (guild_id, auto_translate)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE SET auto_translate = EXCLUDED.auto_translate, updated_at = NOW()
+            "#,
+        )
+        .bind(guild_id)
+        .bind(enabled)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_reaction_translation(pool: &PgPool, guild_id: i64, enabled: bool) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_settings (guild_id, reaction_translation)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE SET reaction_translation = EXCLUDED.reaction_translation, updated_at = NOW()
+            "#,
+        )
+        .bind(guild_id)
+        .bind(enabled)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn update_languages(
         pool: &PgPool,
         guild_id: i64,
@@ -123,8 +316,9 @@ impl HistoryRepository {
         let result = sqlx::query_as::<_, TranslationHistory>(
             r#"
             INSERT INTO translation_history
-            (guild_id, channel_id, user_id, original_message, translated_message, source_language, target_language)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            (guild_id, channel_id, user_id, original_message, translated_message, source_language, target_language,
+             source_message_id, webhook_url, output_format, webhook_message_ids)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             RETURNING *
             "#,
         )
@@ -135,12 +329,93 @@ impl HistoryRepository {
         .bind(&history.translated_message)
         .bind(&history.source_language)
         .bind(&history.target_language)
+        .bind(history.source_message_id)
+        .bind(&history.webhook_url)
+        .bind(&history.output_format)
+        .bind(&history.webhook_message_ids)
         .fetch_one(pool)
         .await?;
 
         Ok(result)
     }
 
+    pub async fn get_by_source_message(
+        pool: &PgPool,
+        source_message_id: i64,
+    ) -> Result<Vec<TranslationHistory>> {
+        let result = sqlx::query_as::<_, TranslationHistory>(
+            "SELECT * FROM translation_history WHERE source_message_id = $1 ORDER BY id",
+        )
+        .bind(source_message_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn update_delivery(
+        pool: &PgPool,
+        id: i64,
+        original_message: &str,
+        translated_message: &str,
+        webhook_message_ids: &[u64],
+    ) -> Result<()> {
+        let ids: Vec<i64> = webhook_message_ids.iter().map(|&id| id as i64).collect();
+
+        sqlx::query(
+            r#"
+            UPDATE translation_history
+            SET original_message = $2, translated_message = $3, webhook_message_ids = $4
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(original_message)
+        .bind(translated_message)
+        .bind(&ids)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_skip(pool: &PgPool, guild_id: i64, channel_id: i64, reason: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO translation_skips (guild_id, channel_id, reason, skip_count)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (guild_id, channel_id, reason)
+            DO UPDATE SET
+                skip_count = translation_skips.skip_count + 1,
+                last_skipped_at = NOW()
+            "#,
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(reason)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn skip_counts(pool: &PgPool, guild_id: i64) -> Result<Vec<(String, i64)>> {
+        let results = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT reason, SUM(skip_count)::BIGINT
+            FROM translation_skips
+            WHERE guild_id = $1
+            GROUP BY reason
+            ORDER BY 2 DESC
+            "#,
+        )
+        .bind(guild_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(results)
+    }
+
     pub async fn get_recent(
         pool: &PgPool,
         channel_id: i64,
@@ -157,3 +432,302 @@ impl HistoryRepository {
         Ok(results)
     }
 }
+
+pub struct JobRepository;
+
+impl JobRepository {
+    /// Stores a job already claimed by `owner`, the instance queueing it.
+    pub async fn create(pool: &PgPool, job: &TranslationJob, owner: &str) -> Result<TranslationJob> {
+        let result = sqlx::query_as::<_, TranslationJob>(
+            r#"
+            INSERT INTO translation_jobs
+            (guild_id, channel_id, message_id, author_id, author_name, display_name, avatar_url, content, jump_url, sent_at,
+             claimed_by, claimed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(job.guild_id)
+        .bind(job.channel_id)
+        .bind(job.message_id)
+        .bind(job.author_id)
+        .bind(&job.author_name)
+        .bind(&job.display_name)
+        .bind(&job.avatar_url)
+        .bind(&job.content)
+        .bind(&job.jump_url)
+        .bind(job.sent_at)
+        .bind(owner)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Claims unfinished jobs nobody owns, plus jobs whose owner stopped
+    /// without releasing them more than `stale_after_secs` ago, oldest first.
+    /// Rows locked by a concurrent claim are skipped, so every job goes to
+    /// exactly one instance.
+    pub async fn claim_pending(
+        pool: &PgPool,
+        owner: &str,
+        stale_after_secs: f64,
+    ) -> Result<Vec<TranslationJob>> {
+        let mut results = sqlx::query_as::<_, TranslationJob>(
+            r#"
+            UPDATE translation_jobs
+            SET claimed_by = $1, claimed_at = NOW()
+            WHERE id IN (
+                SELECT id FROM translation_jobs
+                WHERE claimed_by IS NULL
+                   OR claimed_at < NOW() - make_interval(secs => $2)
+                ORDER BY id
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(owner)
+        .bind(stale_after_secs)
+        .fetch_all(pool)
+        .await?;
+
+        results.sort_by_key(|job| job.id);
+
+        Ok(results)
+    }
+
+    /// Hands the unfinished jobs of `owner` back for the next start.
+    pub async fn release_claims(pool: &PgPool, owner: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE translation_jobs SET claimed_by = NULL, claimed_at = NULL WHERE claimed_by = $1",
+        )
+        .bind(owner)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete(pool: &PgPool, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM translation_jobs WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct UsageRepository;
+
+impl UsageRepository {
+    /// Characters sent to a provider on the given day.
+    pub async fn get(pool: &PgPool, provider: &str, date: NaiveDate) -> Result<Option<i64>> {
+        let result = sqlx::query_scalar(
+            "SELECT characters FROM provider_usage WHERE provider = $1 AND usage_date = $2",
+        )
+        .bind(provider)
+        .bind(date)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn add(pool: &PgPool, provider: &str, date: NaiveDate, characters: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO provider_usage (provider, usage_date, characters, requests)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (provider, usage_date)
+            DO UPDATE SET
+                characters = provider_usage.characters + EXCLUDED.characters,
+                requests = provider_usage.requests + 1
+            "#,
+        )
+        .bind(provider)
+        .bind(date)
+        .bind(characters)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+pub struct ReactionRepository;
+
+impl ReactionRepository {
+    /// Marks a message as translated into `target_language`. Returns `false`
+    /// when another reaction already claimed it.
+    pub async fn claim(pool: &PgPool, message_id: i64, target_language: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reaction_translations (message_id, target_language)
+            VALUES ($1, $2)
+            ON CONFLICT (message_id, target_language) DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(target_language)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gives a claim back so the translation can be retried after a failure.
+    pub async fn release(pool: &PgPool, message_id: i64, target_language: &str) -> Result<()> {
+        sqlx::query("DELETE FROM reaction_translations WHERE message_id = $1 AND target_language = $2")
+            .bind(message_id)
+            .bind(target_language)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_older_than(pool: &PgPool, max_age_secs: f64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM reaction_translations WHERE created_at <= NOW() - make_interval(secs => $1)",
+        )
+        .bind(max_age_secs)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+pub struct PreferenceRepository;
+
+impl PreferenceRepository {
+    pub async fn get(pool: &PgPool, user_id: i64) -> Result<Option<UserPreference>> {
+        let result = sqlx::query_as::<_, UserPreference>(
+            "SELECT * FROM user_preferences WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn set_target_language(
+        pool: &PgPool,
+        user_id: i64,
+        target_language: &str,
+    ) -> Result<UserPreference> {
+        let result = sqlx::query_as::<_, UserPreference>(
+            r#"
+            INSERT INTO user_preferences (user_id, target_language)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id)
+            DO UPDATE SET target_language = EXCLUDED.target_language, updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(target_language)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn delete(pool: &PgPool, user_id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM user_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+pub struct CacheRepository;
+
+impl CacheRepository {
+    pub async fn get(
+        pool: &PgPool,
+        text: &str,
+        source: &str,
+        target: &str,
+        allow_llm: bool,
+        max_age_secs: f64,
+    ) -> Result<Option<CachedTranslation>> {
+        let result = sqlx::query_as::<_, CachedTranslation>(
+            r#"
+            SELECT * FROM translation_cache
+            WHERE text_hash = md5($1)
+              AND source_language = $2
+              AND target_language = $3
+              AND allow_llm = $4
+              AND source_text = $1
+              AND created_at > NOW() - make_interval(secs => $5)
+            "#,
+        )
+        .bind(text)
+        .bind(source)
+        .bind(target)
+        .bind(allow_llm)
+        .bind(max_age_secs)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upsert(
+        pool: &PgPool,
+        text: &str,
+        source: &str,
+        target: &str,
+        allow_llm: bool,
+        translated_text: &str,
+        detected_language: Option<&str>,
+        confidence: Option<f32>,
+        provider: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO translation_cache
+            (text_hash, source_language, target_language, allow_llm, source_text, translated_text, detected_language, confidence, provider)
+            VALUES (md5($1), $2, $3, $4, $1, $5, $6, $7, $8)
+            ON CONFLICT (text_hash, source_language, target_language, allow_llm)
+            DO UPDATE SET
+                source_text = EXCLUDED.source_text,
+                translated_text = EXCLUDED.translated_text,
+                detected_language = EXCLUDED.detected_language,
+                confidence = EXCLUDED.confidence,
+                provider = EXCLUDED.provider,
+                created_at = NOW()
+            "#,
+        )
+        .bind(text)
+        .bind(source)
+        .bind(target)
+        .bind(allow_llm)
+        .bind(translated_text)
+        .bind(detected_language)
+        .bind(confidence)
+        .bind(provider)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_expired(pool: &PgPool, max_age_secs: f64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM translation_cache WHERE created_at <= NOW() - make_interval(secs => $1)",
+        )
+        .bind(max_age_secs)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,410 @@
+use crate::commands::{
+    HelpCommand, LanguagesCommand, MyLangCommand, SetLogCommand, StatsCommand, TranslateCommand,
+};
+use crate::db::DatabasePool;
+use crate::models::{ChannelMode, OutputFormat};
+use crate::services::chunker::{split_message, DISCORD_MESSAGE_LIMIT};
+use crate::services::detect::is_auto;
+use crate::services::languages::{self, LANGUAGES};
+use crate::services::{ChannelConfigCache, GuildSettingsCache, TranslationService};
+use crate::utils::PermissionChecker;
+use anyhow::{anyhow, Result};
+use serenity::all::{
+    ChannelId, ChannelType, Command, CommandInteraction, CommandOptionType, CommandType, Context,
+    CreateAllowedMentions, CreateAutocompleteResponse, CreateCommand, CreateCommandOption,
+    CreateInteractionResponse, CreateInteractionResponseFollowup, EditInteractionResponse,
+    EventHandler, Interaction, Permissions, Ready, ResolvedOption, ResolvedTarget, ResolvedValue,
+};
+use std::sync::Arc;
+use tokio_util::task::TaskTracker;
+
+const TRANSLATE_MESSAGE: &str = "Translate message";
+
+/// Commands whose output is only shown to the user who ran them.
+const EPHEMERAL_COMMANDS: &[&str] = &["set-log", "remove-log", "list-logs", TRANSLATE_MESSAGE];
+
+/// Discord caps autocomplete suggestions at 25.
+const AUTOCOMPLETE_LIMIT: usize = 25;
+
+pub struct InteractionHandler {
+    db: DatabasePool,
+    translation_service: Arc<TranslationService>,
+    channels: Arc<ChannelConfigCache>,
+    settings: Arc<GuildSettingsCache>,
+    /// Tracks running handler work so shutdown can wait for it.
+    tasks: TaskTracker,
+}
+
+impl InteractionHandler {
+    pub fn new(
+        db: DatabasePool,
+        translation_service: Arc<TranslationService>,
+        channels: Arc<ChannelConfigCache>,
+        settings: Arc<GuildSettingsCache>,
+        tasks: TaskTracker,
+    ) -> Self {
+        Self {
+            db,
+            translation_service,
+            channels,
+            settings,
+            tasks,
+        }
+    }
+
+    fn commands() -> Vec<CreateCommand> {
+        let admin = Permissions::MANAGE_CHANNELS;
+
+        vec![
+            CreateCommand::new("translate")
+                .description("Translate a piece of text")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "text",
+                        "Text to translate",
+                    )
+                    .required(true),
+                )
+                .add_option(Self::language_option(
+                    "target",
+                    "Language to translate into (your preferred language when omitted)",
+                ))
+                .add_option(Self::language_option(
+                    "source",
+                    "Language of the text (detected when omitted)",
+                )),
+            CreateCommand::new("set-log")
+                .description("Set up translation logging for a channel")
+                .default_member_permissions(admin)
+                .dm_permission(false)
+                .add_option(
+                    Self::language_option("source", "Language to translate from, or auto")
+                        .required(true),
+                )
+                .add_option(
+                    Self::language_option("target", "Language to translate into").required(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Channel,
+                        "channel",
+                        "Channel to translate",
+                    )
+                    .channel_types(vec![ChannelType::Text, ChannelType::News])
+                    .required(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "webhook",
+                        "Webhook URL that receives the translations",
+                    )
+                    .required(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::String, "format", "Output format")
+                        .add_string_choice("Plain text", "plain")
+                        .add_string_choice("Embed", "embed")
+                        .add_string_choice("Impersonate author", "impersonate"),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "mode",
+                        "Translation direction",
+                    )
+                    .add_string_choice("One way", "oneway")
+                    .add_string_choice("Bidirectional", "bidirectional"),
+                ),
+            CreateCommand::new("remove-log")
+                .description("Remove translation logging from a channel")
+                .default_member_permissions(admin)
+                .dm_permission(false)
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Channel,
+                        "channel",
+                        "Channel to stop translating",
+                    )
+                    .required(true),
+                ),
+            CreateCommand::new("list-logs")
+                .description("List the translation channels configured in this server")
+                .default_member_permissions(admin)
+                .dm_permission(false),
+            CreateCommand::new("stats")
+                .description("Show translation statistics for this server")
+                .dm_permission(false),
+            CreateCommand::new("languages").description("Show all supported languages"),
+            CreateCommand::new("help").description("Show how to use the bot"),
+            CreateCommand::new(TRANSLATE_MESSAGE).kind(CommandType::Message),
+        ]
+    }
+
+    fn language_option(name: &str, description: &str) -> CreateCommandOption {
+        CreateCommandOption::new(CommandOptionType::String, name, description)
+            .set_autocomplete(true)
+    }
+
+    async fn handle_command(&self, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+        let ephemeral = EPHEMERAL_COMMANDS.contains(&command.data.name.as_str());
+
+        // Translations can outlast the three seconds Discord allows for the
+        // initial response, so every command is deferred up front.
+        if ephemeral {
+            command.defer_ephemeral(&ctx.http).await?;
+        } else {
+            command.defer(&ctx.http).await?;
+        }
+
+        let reply = match self.run_command(ctx, command).await {
+            Ok(reply) => reply,
+            Err(e) => format!("❌ {}", e),
+        };
+
+        let mut pieces = split_message(&reply, DISCORD_MESSAGE_LIMIT).into_iter();
+
+        if let Some(first) = pieces.next() {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(first)
+                        .allowed_mentions(CreateAllowedMentions::new()),
+                )
+                .await?;
+        }
+
+        for piece in pieces {
+            command
+                .create_followup(
+                    &ctx.http,
+                    CreateInteractionResponseFollowup::new()
+                        .content(piece)
+                        .ephemeral(ephemeral)
+                        .allowed_mentions(CreateAllowedMentions::new()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run_command(&self, ctx: &Context, command: &CommandInteraction) -> Result<String> {
+        let options = command.data.options();
+
+        match command.data.name.as_str() {
+            "translate" => {
+                let text =
+                    string_option(&options, "text").ok_or_else(|| anyhow!("Missing text"))?;
+                let target = match string_option(&options, "target") {
+                    Some(target) => SetLogCommand::parse_language(target)?,
+                    None => self.default_target(command).await,
+                };
+                if is_auto(&target) {
+                    return Err(anyhow!("The target language cannot be `auto`."));
+                }
+                let source = match string_option(&options, "source") {
+                    Some(source) => SetLogCommand::parse_language(source)?,
+                    None => "auto".to_string(),
+                };
+
+                Ok(TranslateCommand::run(
+                    &self.settings,
+                    &self.translation_service,
+                    command.guild_id,
+                    text,
+                    &source,
+                    &target,
+                )
+                .await)
+            }
+            "set-log" => {
+                Self::require_admin(command)?;
+                let guild_id = Self::guild_id(command)?;
+
+                let channel_id = channel_option(&options, "channel")
+                    .ok_or_else(|| anyhow!("Missing channel"))?;
+                let format = match string_option(&options, "format") {
+                    Some(value) => OutputFormat::parse(value)
+                        .ok_or_else(|| anyhow!("Unknown format `{}`.", value))?,
+                    None => OutputFormat::Plain,
+                };
+                let mode = match string_option(&options, "mode") {
+                    Some(value) => ChannelMode::parse(value)
+                        .ok_or_else(|| anyhow!("Unknown mode `{}`.", value))?,
+                    None => ChannelMode::OneWay,
+                };
+
+                let channel = SetLogCommand::build_channel(
+                    guild_id,
+                    channel_id.get() as i64,
+                    string_option(&options, "source")
+                        .ok_or_else(|| anyhow!("Missing source language"))?,
+                    string_option(&options, "target")
+                        .ok_or_else(|| anyhow!("Missing target language"))?,
+                    string_option(&options, "webhook")
+                        .ok_or_else(|| anyhow!("Missing webhook URL"))?,
+                    format,
+                    mode,
+                )?;
+
+                SetLogCommand::save(ctx, &self.db, &self.channels, &channel).await
+            }
+            "remove-log" => {
+                Self::require_admin(command)?;
+                let guild_id = Self::guild_id(command)?;
+                let channel_id = channel_option(&options, "channel")
+                    .ok_or_else(|| anyhow!("Missing channel"))?;
+
+                SetLogCommand::remove(&self.db, &self.channels, guild_id, channel_id.get() as i64)
+                    .await
+            }
+            "list-logs" => {
+                Self::require_admin(command)?;
+                SetLogCommand::list(&self.db, Self::guild_id(command)?).await
+            }
+            "stats" => {
+                StatsCommand::report(
+                    &self.db,
+                    Self::guild_id(command)?,
+                    &self.translation_service,
+                )
+                .await
+            }
+            TRANSLATE_MESSAGE => {
+                let Some(ResolvedTarget::Message(message)) = command.data.target() else {
+                    return Err(anyhow!("No message selected"));
+                };
+                let target = self.default_target(command).await;
+
+                Ok(TranslateCommand::translate_message(
+                    &self.settings,
+                    &self.translation_service,
+                    command.guild_id,
+                    &message.content,
+                    &target,
+                )
+                .await)
+            }
+            "languages" => Ok(LanguagesCommand::text()),
+            "help" => Ok(HelpCommand::text().to_string()),
+            other => Err(anyhow!("Unknown command `{}`", other)),
+        }
+    }
+
+    async fn handle_autocomplete(&self, ctx: &Context, command: &CommandInteraction) -> Result<()> {
+        let Some(focused) = command.data.autocomplete() else {
+            return Ok(());
+        };
+
+        let query = focused.value.trim().to_lowercase();
+        let mut response = CreateAutocompleteResponse::new();
+        let mut remaining = AUTOCOMPLETE_LIMIT;
+
+        if focused.name == "source" && "auto".starts_with(&query) {
+            response = response.add_string_choice("Detect automatically", "auto");
+            remaining -= 1;
+        }
+
+        let matches = LANGUAGES.iter().filter(|language| {
+            query.is_empty()
+                || language.code.to_lowercase().starts_with(&query)
+                || language.name.to_lowercase().contains(&query)
+        });
+
+        for language in matches.take(remaining) {
+            response = response.add_string_choice(
+                format!("{} ({})", language.name, language.code),
+                language.code,
+            );
+        }
+
+        command
+            .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+            .await?;
+
+        Ok(())
+    }
+
+    /// The caller's `;mylang` preference, falling back to their Discord
+    /// client language.
+    async fn default_target(&self, command: &CommandInteraction) -> String {
+        match MyLangCommand::preferred_language(&self.db, command.user.id).await {
+            Some(language) => language,
+            None => languages::from_locale(&command.locale).code.to_string(),
+        }
+    }
+
+    /// Discord hides admin commands via default permissions, but server
+    /// owners can override that, so the bot still checks on every call.
+    fn require_admin(command: &CommandInteraction) -> Result<()> {
+        let allowed = command
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(PermissionChecker::grants_admin);
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "You need Administrator, Manage Server, or Manage Channels permission to use this command."
+            ))
+        }
+    }
+
+    fn guild_id(command: &CommandInteraction) -> Result<i64> {
+        command
+            .guild_id
+            .map(|guild_id| guild_id.get() as i64)
+            .ok_or_else(|| anyhow!("This command can only be used in a server"))
+    }
+}
+
+fn string_option<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match option.value {
+            ResolvedValue::String(value) => Some(value),
+            _ => None,
+        })
+}
+
+fn channel_option(options: &[ResolvedOption<'_>], name: &str) -> Option<ChannelId> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.value {
+            ResolvedValue::Channel(channel) => Some(channel.id),
+            _ => None,
+        })
+}
+
+#[async_trait::async_trait]
+impl EventHandler for InteractionHandler {
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        self.tasks
+            .track_future(async {
+                let result = match &interaction {
+                    Interaction::Command(command) => self.handle_command(&ctx, command).await,
+                    Interaction::Autocomplete(command) => self.handle_autocomplete(&ctx, command).await,
+                    _ => Ok(()),
+                };
+
+                if let Err(e) = result {
+                    tracing::error!("Interaction error: {}", e);
+                }
+            })
+            .await;
+    }
+
+    async fn ready(&self, ctx: Context, _ready: Ready) {
+        match Command::set_global_commands(&ctx.http, Self::commands()).await {
+            Ok(commands) => tracing::info!("Registered {} slash commands", commands.len()),
+            Err(e) => tracing::error!("Failed to register slash commands: {}", e),
+        }
+    }
+}
@@ -1,32 +1,70 @@
-use crate::commands::{HelpCommand, SetLogCommand, StatsCommand};
-use crate::db::{ChannelRepository, DatabasePool, HistoryRepository};
-use crate::models::TranslationHistory;
-use crate::services::TranslationService;
+use crate::commands::{
+    ConfigCommand, HelpCommand, LanguagesCommand, LlmCommand, MyLangCommand, ProvidersCommand,
+    ReactionsCommand, SetLogCommand, StatsCommand, TranslateCommand,
+};
+use crate::db::{DatabasePool, HistoryRepository};
+use crate::models::{TranslationHistory, TranslationJob};
+use crate::services::filter::{self, SkipReason};
+use crate::services::webhook::WebhookClient;
+use crate::services::worker::translation_log;
+use crate::services::{ChannelConfigCache, GuildSettingsCache, JobQueue, TranslationService};
 use anyhow::Result;
-use serenity::all::{Context, EventHandler, Message, Ready};
+use chrono::{DateTime, Utc};
+use serenity::all::{
+    ChannelId, Context, EventHandler, GuildId, Message, MessageId, MessageUpdateEvent, Ready,
+};
 use std::sync::Arc;
+use tokio_util::task::TaskTracker;
+
+const DEFAULT_PREFIX: &str = ";";
 
 pub struct MessageHandler {
     db: DatabasePool,
     translation_service: Arc<TranslationService>,
+    channels: Arc<ChannelConfigCache>,
+    settings: Arc<GuildSettingsCache>,
+    jobs: JobQueue,
+    webhook: WebhookClient,
+    /// Tracks running handler work so shutdown can wait for it.
+    tasks: TaskTracker,
 }
 
 impl MessageHandler {
-    pub fn new(db: DatabasePool, translation_service: Arc<TranslationService>) -> Self {
+    pub fn new(
+        db: DatabasePool,
+        translation_service: Arc<TranslationService>,
+        channels: Arc<ChannelConfigCache>,
+        settings: Arc<GuildSettingsCache>,
+        jobs: JobQueue,
+        tasks: TaskTracker,
+    ) -> Self {
         Self {
             db,
             translation_service,
+            channels,
+            settings,
+            jobs,
+            webhook: WebhookClient::new(),
+            tasks,
+        }
+    }
+
+    /// The guild's command prefix, `;` in DMs and for unconfigured guilds.
+    async fn prefix(&self, guild_id: Option<GuildId>) -> String {
+        match guild_id {
+            Some(guild_id) => self.settings.get(guild_id).await.prefix,
+            None => DEFAULT_PREFIX.to_string(),
         }
     }
 
-    async fn handle_command(&self, ctx: &Context, msg: &Message) -> Result<()> {
+    async fn handle_command(&self, ctx: &Context, msg: &Message, prefix: &str) -> Result<()> {
         let content = msg.content.trim();
 
-        if !content.starts_with(';') {
+        let Some(body) = content.strip_prefix(prefix) else {
             return Ok(());
-        }
+        };
 
-        let parts: Vec<&str> = content[1..].split_whitespace().collect();
+        let parts: Vec<&str> = body.split_whitespace().collect();
         if parts.is_empty() {
             return Ok(());
         }
@@ -39,22 +77,66 @@ impl MessageHandler {
                 HelpCommand::execute(ctx, msg).await?;
             }
             "set-log" => {
-                SetLogCommand::execute(ctx, msg, args.to_vec(), &self.db).await?;
+                SetLogCommand::execute(
+                    ctx,
+                    msg,
+                    args.to_vec(),
+                    &self.db,
+                    &self.channels,
+                    &self.settings,
+                )
+                .await?;
+            }
+            "edit-log" => {
+                SetLogCommand::edit_log(ctx, msg, args.to_vec(), &self.db, &self.channels)
+                    .await?;
             }
             "remove-log" => {
-                SetLogCommand::remove_log(ctx, msg, args.to_vec(), &self.db).await?;
+                SetLogCommand::remove_log(ctx, msg, args.to_vec(), &self.db, &self.channels)
+                    .await?;
             }
             "list-logs" => {
                 SetLogCommand::list_logs(ctx, msg, &self.db).await?;
             }
             "translate" => {
-                self.handle_manual_translation(ctx, msg, args).await?;
+                TranslateCommand::execute(
+                    ctx,
+                    msg,
+                    args.to_vec(),
+                    &self.db,
+                    &self.settings,
+                    &self.translation_service,
+                )
+                .await?;
             }
             "languages" | "langs" => {
-                self.show_languages(ctx, msg).await?;
+                LanguagesCommand::execute(ctx, msg).await?;
             }
             "stats" => {
-                StatsCommand::execute(ctx, msg, &self.db).await?;
+                StatsCommand::execute(ctx, msg, &self.db, &self.translation_service).await?;
+            }
+            "providers" => {
+                ProvidersCommand::execute(ctx, msg, &self.translation_service).await?;
+            }
+            "add-output" => {
+                SetLogCommand::add_output(ctx, msg, args.to_vec(), &self.db, &self.channels)
+                    .await?;
+            }
+            "remove-output" => {
+                SetLogCommand::remove_output(ctx, msg, args.to_vec(), &self.db, &self.channels)
+                    .await?;
+            }
+            "mylang" => {
+                MyLangCommand::execute(ctx, msg, args.to_vec(), &self.db).await?;
+            }
+            "reactions" => {
+                ReactionsCommand::execute(ctx, msg, args.to_vec(), &self.db, &self.settings).await?;
+            }
+            "llm" => {
+                LlmCommand::execute(ctx, msg, args.to_vec(), &self.db, &self.settings).await?;
+            }
+            "config" => {
+                ConfigCommand::execute(ctx, msg, args.to_vec(), &self.db, &self.settings).await?;
             }
             _ => {}
         }
@@ -62,170 +144,271 @@ impl MessageHandler {
         Ok(())
     }
 
-    async fn handle_manual_translation(&self, ctx: &Context, msg: &Message, args: &[&str]) -> Result<()> {
-        if args.len() < 3 {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    "Usage: `;translate <source-lang> <target-lang> <text>`\nExample: `;translate zh en 你好世界`",
-                )
-                .await?;
+    async fn handle_auto_translation(&self, msg: &Message) -> Result<()> {
+        if msg.author.bot {
             return Ok(());
         }
 
-        let source_lang = args[0];
-        let target_lang = args[1];
-        let text = args[2..].join(" ");
-
-        let typing = msg.channel_id.start_typing(&ctx.http);
+        let Some(guild_id) = msg.guild_id else {
+            return Ok(());
+        };
 
-        match self
-            .translation_service
-            .translate_with_fallback(&text, source_lang, target_lang)
-            .await
-        {
-            Ok(translated) => {
-                msg.channel_id
-                    .say(
-                        &ctx.http,
-                        format!("**Translation ({} → {}):**\n{}", source_lang, target_lang, translated),
-                    )
-                    .await?;
-            }
-            Err(e) => {
-                msg.channel_id
-                    .say(&ctx.http, format!("❌ Translation failed: {}", e))
-                    .await?;
-            }
+        // A config stored under another guild must never receive this
+        // guild's messages.
+        match self.channels.get(msg.channel_id) {
+            Some(cached) if cached.config.guild_id == guild_id.get() as i64 => {}
+            _ => return Ok(()),
         }
 
-        typing.stop();
-
-        Ok(())
-    }
-
-    async fn show_languages(&self, ctx: &Context, msg: &Message) -> Result<()> {
-        let langs = r#"
-**Supported Languages:**
+        if !self.settings.get(guild_id).await.auto_translate {
+            return Ok(());
+        }
 
-**Chinese:**
-• `zh`, `zh-CN`, `zh-Hans` - Simplified Chinese
-• `zh-TW`, `zh-Hant` - Traditional Chinese
+        if let Some(reason) = filter::classify(&msg.content) {
+            self.record_skip(msg, reason).await;
+            return Ok(());
+        }
 
-**Other Languages:**
-• `en` - English
-• `ja` - Japanese
-• `ko` - Korean
-• `de` - German
-• `fr` - French
-• `es` - Spanish
-• `it` - Italian
-• `pt` - Portuguese
-• `ru` - Russian
-• `nl` - Dutch
-• `pl` - Polish
-        "#;
+        let nick = msg.member.as_ref().and_then(|member| member.nick.clone());
 
-        msg.channel_id.say(&ctx.http, langs).await?;
+        if !self.jobs.enqueue(snapshot(msg, Some(guild_id), nick)).await? {
+            tracing::warn!("Translation queue full, dropping message {}", msg.id);
+            self.record_skip(msg, SkipReason::QueueFull).await;
+        }
 
         Ok(())
     }
 
-    async fn handle_auto_translation(&self, ctx: &Context, msg: &Message) -> Result<()> {
-        if msg.author.bot {
+    /// Re-translates an edited message and rewrites every translation that
+    /// was posted for it, or removes them when the edit left nothing to
+    /// translate.
+    async fn sync_edit(&self, ctx: &Context, event: &MessageUpdateEvent) -> Result<()> {
+        // Link previews and other embed-only updates carry no content.
+        let Some(content) = &event.content else {
+            return Ok(());
+        };
+
+        if self.channels.get(event.channel_id).is_none() {
             return Ok(());
         }
 
-        let channel_config =
-            ChannelRepository::get_by_channel_id(self.db.pool(), msg.channel_id.get() as i64).await?;
+        let deliveries = self.posted_deliveries(event.id).await?;
 
-        if let Some(config) = channel_config {
-            let typing = msg.channel_id.start_typing(&ctx.http);
+        match edit_sync(&deliveries, content) {
+            EditSync::Unchanged => return Ok(()),
+            EditSync::Retract => {
+                self.retract(event.id, deliveries).await;
+                return Ok(());
+            }
+            EditSync::Retranslate => {}
+        }
 
-            match self
+        let msg = event.channel_id.message(&ctx.http, event.id).await?;
+        let guild_id = event.guild_id;
+
+        let display_name = match guild_id {
+            Some(guild_id) => guild_id
+                .member(ctx, msg.author.id)
+                .await
+                .ok()
+                .and_then(|member| member.nick),
+            None => None,
+        };
+        let allow_llm = LlmCommand::is_allowed(&self.settings, guild_id).await;
+        let source = snapshot(&msg, guild_id, display_name);
+
+        for delivery in deliveries {
+            let Some(webhook_url) = &delivery.webhook_url else {
+                continue;
+            };
+
+            let response = match self
                 .translation_service
-                .translate_with_fallback(
-                    &msg.content,
-                    &config.source_language,
-                    &config.target_language,
-                )
+                .translate_text(content, &delivery.source_language, &delivery.target_language, allow_llm)
                 .await
             {
-                Ok(translated) => {
-                    let username = if let Some(discrim) = msg.author.discriminator {
-                        format!("{}#{}", msg.author.name, discrim)
-                    } else {
-                        msg.author.name.clone()
-                    };
-                    let user_id = msg.author.id.get();
-
-                    let webhook_message = format!(
-                        "{} (ID: {}) sent this:\n{}\n\nWhich translates to this:\n{}",
-                        username, user_id, msg.content, translated
-                    );
-
-                    self.send_to_webhook(&config.webhook_url, &webhook_message).await?;
-
-                    if let Some(guild_id) = msg.guild_id {
-                        let history = TranslationHistory::new(
-                            guild_id.get() as i64,
-                            msg.channel_id.get() as i64,
-                            msg.author.id.get() as i64,
-                            msg.content.clone(),
-                            translated,
-                            config.source_language.clone(),
-                            config.target_language.clone(),
-                        );
-
-                        HistoryRepository::create(self.db.pool(), &history).await.ok();
-                    }
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::error!("Re-translation of edited message {} failed: {}", event.id, e);
+                    continue;
+                }
+            };
+
+            let log = translation_log(
+                &source,
+                response,
+                &delivery.source_language,
+                &delivery.target_language,
+            );
+
+            match self
+                .webhook
+                .edit(webhook_url, delivery.output_format(), &log, &delivery.message_ids())
+                .await
+            {
+                Ok(message_ids) => {
+                    HistoryRepository::update_delivery(
+                        self.db.pool(),
+                        delivery.id,
+                        &log.original,
+                        &log.translated,
+                        &message_ids,
+                    )
+                    .await?;
                 }
                 Err(e) => {
-                    tracing::error!("Translation failed for channel {}: {}", msg.channel_id, e);
+                    tracing::error!("Failed to update translation of message {}: {}", event.id, e);
                 }
             }
+        }
+
+        Ok(())
+    }
 
-            typing.stop();
+    /// Removes every translation that was posted for a deleted message.
+    async fn sync_delete(&self, channel_id: ChannelId, message_id: MessageId) -> Result<()> {
+        if self.channels.get(channel_id).is_none() {
+            return Ok(());
         }
 
+        let deliveries = self.posted_deliveries(message_id).await?;
+        self.retract(message_id, deliveries).await;
+
         Ok(())
     }
 
-    async fn send_to_webhook(&self, webhook_url: &str, content: &str) -> Result<()> {
-        let client = reqwest::Client::new();
+    /// Translations of a message that are still posted.
+    async fn posted_deliveries(&self, message_id: MessageId) -> Result<Vec<TranslationHistory>> {
+        let deliveries =
+            HistoryRepository::get_by_source_message(self.db.pool(), message_id.get() as i64).await?;
 
-        let payload = serde_json::json!({
-            "content": content,
-            "username": "MegaChinese Translation",
-        });
+        Ok(deliveries
+            .into_iter()
+            .filter(TranslationHistory::is_posted)
+            .collect())
+    }
 
-        client
-            .post(webhook_url)
-            .json(&payload)
-            .send()
-            .await?
-            .error_for_status()?;
+    async fn retract(&self, message_id: MessageId, deliveries: Vec<TranslationHistory>) {
+        for delivery in deliveries {
+            let Some(webhook_url) = &delivery.webhook_url else {
+                continue;
+            };
 
-        Ok(())
+            if let Err(e) = self.webhook.delete(webhook_url, &delivery.message_ids()).await {
+                tracing::error!("Failed to delete translation of message {}: {}", message_id, e);
+                continue;
+            }
+
+            if let Err(e) = HistoryRepository::update_delivery(
+                self.db.pool(),
+                delivery.id,
+                &delivery.original_message,
+                &delivery.translated_message,
+                &[],
+            )
+            .await
+            {
+                tracing::error!("Failed to record deleted translation of {}: {}", message_id, e);
+            }
+        }
+    }
+
+    async fn record_skip(&self, msg: &Message, reason: SkipReason) {
+        tracing::debug!("Skipping message {}: {}", msg.id, reason.as_str());
+
+        let Some(guild_id) = msg.guild_id else {
+            return;
+        };
+
+        if let Err(e) = HistoryRepository::record_skip(
+            self.db.pool(),
+            guild_id.get() as i64,
+            msg.channel_id.get() as i64,
+            reason.as_str(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to record skipped message: {}", e);
+        }
     }
 }
 
 #[async_trait::async_trait]
 impl EventHandler for MessageHandler {
     async fn message(&self, ctx: Context, msg: Message) {
-        if msg.author.bot {
-            return;
-        }
+        self.tasks
+            .track_future(async {
+                if msg.author.bot {
+                    return;
+                }
 
-        if let Err(e) = self.handle_command(&ctx, &msg).await {
-            tracing::error!("Command error: {}", e);
-        }
+                let prefix = self.prefix(msg.guild_id).await;
 
-        if !msg.content.starts_with(';') {
-            if let Err(e) = self.handle_auto_translation(&ctx, &msg).await {
-                tracing::error!("Auto-translation error: {}", e);
-            }
-        }
+                if let Err(e) = self.handle_command(&ctx, &msg, &prefix).await {
+                    tracing::error!("Command error: {}", e);
+                }
+
+                if !msg.content.starts_with(prefix.as_str()) {
+                    if let Err(e) = self.handle_auto_translation(&msg).await {
+                        tracing::error!("Auto-translation error: {}", e);
+                    }
+                }
+            })
+            .await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        self.tasks
+            .track_future(async {
+                if event.author.as_ref().is_some_and(|author| author.bot) {
+                    return;
+                }
+
+                if let Err(e) = self.sync_edit(&ctx, &event).await {
+                    tracing::error!("Edit sync error: {}", e);
+                }
+            })
+            .await;
+    }
+
+    async fn message_delete(
+        &self,
+        _ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        self.tasks
+            .track_future(async {
+                if let Err(e) = self.sync_delete(channel_id, deleted_message_id).await {
+                    tracing::error!("Delete sync error: {}", e);
+                }
+            })
+            .await;
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        _ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        _guild_id: Option<GuildId>,
+    ) {
+        self.tasks
+            .track_future(async {
+                for message_id in multiple_deleted_messages_ids {
+                    if let Err(e) = self.sync_delete(channel_id, message_id).await {
+                        tracing::error!("Delete sync error: {}", e);
+                    }
+                }
+            })
+            .await;
     }
 
     async fn ready(&self, _ctx: Context, ready: Ready) {
@@ -233,3 +416,119 @@ impl EventHandler for MessageHandler {
         tracing::info!("Ready to translate!");
     }
 }
+
+#[derive(Debug, PartialEq, Eq)]
+enum EditSync {
+    Unchanged,
+    Retranslate,
+    /// The edit left nothing worth translating, so the old translations go.
+    Retract,
+}
+
+/// What an edit to `content` means for the translations still posted.
+fn edit_sync(deliveries: &[TranslationHistory], content: &str) -> EditSync {
+    if deliveries
+        .iter()
+        .all(|delivery| delivery.original_message == content)
+    {
+        EditSync::Unchanged
+    } else if filter::classify(content).is_some() {
+        EditSync::Retract
+    } else {
+        EditSync::Retranslate
+    }
+}
+
+/// Captures what translating `msg` needs once the gateway event is gone.
+/// `nick` is the author's server nickname, which only comes with gateway
+/// messages and has to be fetched for edits.
+fn snapshot(msg: &Message, guild_id: Option<GuildId>, nick: Option<String>) -> TranslationJob {
+    let author_name = if let Some(discrim) = msg.author.discriminator {
+        format!("{}#{}", msg.author.name, discrim)
+    } else {
+        msg.author.name.clone()
+    };
+
+    let display_name = nick
+        .or_else(|| msg.author.global_name.clone())
+        .unwrap_or_else(|| msg.author.name.clone());
+
+    TranslationJob {
+        id: 0,
+        guild_id: guild_id.map_or(0, |guild_id| guild_id.get() as i64),
+        channel_id: msg.channel_id.get() as i64,
+        message_id: msg.id.get() as i64,
+        author_id: msg.author.id.get() as i64,
+        author_name,
+        display_name,
+        avatar_url: Some(msg.author.face()),
+        content: msg.content.clone(),
+        jump_url: msg.link(),
+        sent_at: DateTime::from_timestamp(msg.timestamp.unix_timestamp(), 0)
+            .unwrap_or_else(Utc::now),
+        created_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OutputFormat;
+
+    fn delivery(original: &str, message_ids: &[u64]) -> TranslationHistory {
+        TranslationHistory::new(
+            1,
+            2,
+            3,
+            original.to_string(),
+            "translated".to_string(),
+            "zh".to_string(),
+            "en".to_string(),
+        )
+        .with_delivery(
+            4,
+            "https://discord.com/api/webhooks/1/token".to_string(),
+            OutputFormat::Plain,
+            message_ids,
+        )
+    }
+
+    #[test]
+    fn unchanged_content_needs_no_sync() {
+        let deliveries = [delivery("你好", &[10]), delivery("你好", &[11])];
+
+        assert_eq!(edit_sync(&deliveries, "你好"), EditSync::Unchanged);
+        assert_eq!(edit_sync(&[], "你好"), EditSync::Unchanged);
+    }
+
+    #[test]
+    fn changed_content_is_retranslated() {
+        let deliveries = [delivery("你好", &[10])];
+
+        assert_eq!(edit_sync(&deliveries, "你好世界"), EditSync::Retranslate);
+    }
+
+    #[test]
+    fn edit_to_untranslatable_content_retracts() {
+        let deliveries = [delivery("你好", &[10])];
+
+        assert_eq!(edit_sync(&deliveries, "https://example.com"), EditSync::Retract);
+        assert_eq!(edit_sync(&deliveries, "<@123456789>"), EditSync::Retract);
+    }
+
+    #[test]
+    fn only_posted_deliveries_are_synced() {
+        assert!(delivery("你好", &[10]).is_posted());
+        assert!(!delivery("你好", &[]).is_posted());
+        assert!(!TranslationHistory::new(
+            1,
+            2,
+            3,
+            "你好".to_string(),
+            "hello".to_string(),
+            "zh".to_string(),
+            "en".to_string(),
+        )
+        .is_posted());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod interaction;
 pub mod message;
+pub mod reaction;
 
+pub use interaction::InteractionHandler;
 pub use message::MessageHandler;
+pub use reaction::ReactionHandler;
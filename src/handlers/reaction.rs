@@ -0,0 +1,161 @@
+use crate::commands::{MyLangCommand, TranslateCommand};
+use crate::db::{DatabasePool, ReactionRepository};
+use crate::services::chunker::{split_message, DISCORD_MESSAGE_LIMIT};
+use crate::services::filter;
+use crate::services::languages;
+use crate::services::{GuildSettingsCache, TranslationService};
+use anyhow::Result;
+use serenity::all::{
+    Context, CreateAllowedMentions, CreateMessage, EventHandler, Reaction, ReactionType,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+/// Reacting with this translates into the reacting user's `;mylang` language.
+const PREFERRED_LANGUAGE_EMOJI: &str = "🌐";
+
+/// How long a message stays marked as translated for a flag.
+const CLAIM_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Replies with a translation when someone reacts to a message with a
+/// country flag or 🌐, in guilds that opted in.
+pub struct ReactionHandler {
+    db: DatabasePool,
+    translation_service: Arc<TranslationService>,
+    settings: Arc<GuildSettingsCache>,
+    /// Tracks running handler work so shutdown can wait for it.
+    tasks: TaskTracker,
+}
+
+impl ReactionHandler {
+    /// Forgets claims old enough that nobody is likely to react anymore.
+    pub async fn purge_claims(db: &DatabasePool) {
+        match ReactionRepository::purge_older_than(db.pool(), CLAIM_RETENTION.as_secs_f64()).await
+        {
+            Ok(removed) if removed > 0 => {
+                tracing::info!("Purged {} old reaction translation claims", removed);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Reaction claim purge failed: {}", e),
+        }
+    }
+
+    pub fn new(
+        db: DatabasePool,
+        translation_service: Arc<TranslationService>,
+        settings: Arc<GuildSettingsCache>,
+        tasks: TaskTracker,
+    ) -> Self {
+        Self {
+            db,
+            translation_service,
+            settings,
+            tasks,
+        }
+    }
+
+    async fn handle_reaction(&self, ctx: &Context, reaction: &Reaction) -> Result<()> {
+        let Some(guild_id) = reaction.guild_id else {
+            return Ok(());
+        };
+
+        if reaction.user_id == Some(ctx.cache.current_user().id) {
+            return Ok(());
+        }
+
+        if reaction
+            .member
+            .as_ref()
+            .is_some_and(|member| member.user.bot)
+        {
+            return Ok(());
+        }
+
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return Ok(());
+        };
+
+        if !self.settings.get(guild_id).await.reaction_translation {
+            return Ok(());
+        }
+
+        let target = if emoji == PREFERRED_LANGUAGE_EMOJI {
+            let Some(user_id) = reaction.user_id else {
+                return Ok(());
+            };
+            match MyLangCommand::preferred_language(&self.db, user_id).await {
+                Some(language) => language,
+                None => return Ok(()),
+            }
+        } else {
+            match languages::from_flag(emoji) {
+                Some(language) => language.code.to_string(),
+                None => return Ok(()),
+            }
+        };
+
+        let message = reaction.message(&ctx.http).await?;
+
+        if message.author.bot || filter::classify(&message.content).is_some() {
+            return Ok(());
+        }
+
+        let message_id = message.id.get() as i64;
+
+        if !ReactionRepository::claim(self.db.pool(), message_id, &target).await? {
+            return Ok(());
+        }
+
+        let reply = match TranslateCommand::message_translation(
+            &self.settings,
+            &self.translation_service,
+            Some(guild_id),
+            &message.content,
+            &target,
+        )
+        .await
+        {
+            Ok(reply) => reply,
+            Err(e) => {
+                // Nothing is posted, so a later reaction may try again.
+                ReactionRepository::release(self.db.pool(), message_id, &target).await?;
+                return Err(e);
+            }
+        };
+
+        for piece in split_message(&reply, DISCORD_MESSAGE_LIMIT) {
+            let sent = message
+                .channel_id
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new()
+                        .content(piece)
+                        .reference_message(&message)
+                        .allowed_mentions(CreateAllowedMentions::new().replied_user(false)),
+                )
+                .await;
+
+            if let Err(e) = sent {
+                // Let a later reaction try again instead of staying claimed.
+                ReactionRepository::release(self.db.pool(), message_id, &target).await?;
+                return Err(e.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for ReactionHandler {
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        self.tasks
+            .track_future(async {
+                if let Err(e) = self.handle_reaction(&ctx, &reaction).await {
+                    tracing::error!("Reaction translation error: {}", e);
+                }
+            })
+            .await;
+    }
+}
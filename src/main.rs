@@ -7,12 +7,17 @@ mod utils;
 
 use anyhow::Result;
 use db::DatabasePool;
-use handlers::MessageHandler;
+use handlers::{InteractionHandler, MessageHandler, ReactionHandler};
 use serenity::all::{Client, GatewayIntents};
-use services::{LibreTranslateProvider, LingvaProvider, MyMemoryProvider, TranslationService};
+use services::{
+    build_provider, BreakerPolicy, ChannelConfigCache, GuildSettingsCache, JobQueue,
+    ProviderLimits, TranslationCache, TranslationService, TranslationWorker,
+};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utils::Config;
+use utils::{shutdown, Config};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,31 +40,128 @@ async fn main() -> Result<()> {
     tracing::info!("Database health check passed");
 
     let mut translation_service = TranslationService::new();
+    translation_service.set_breaker_policy(BreakerPolicy {
+        failure_threshold: config.breaker.failure_threshold,
+        cooldown: config.breaker.cooldown,
+    });
+    translation_service.set_usage_store(db.clone());
+
+    for provider_config in config.providers.iter().filter(|p| p.enabled) {
+        let provider = build_provider(provider_config)?;
+        let limits = ProviderLimits::from_config(provider_config);
+        tracing::info!(
+            "{} provider configured (position {}, timeout {}s, rate limit: {}, daily quota: {})",
+            provider.name(),
+            translation_service.provider_names().len() + 1,
+            provider_config.timeout_secs,
+            limits
+                .requests_per_minute
+                .map_or("none".to_string(), |rate| format!("{}/min", rate)),
+            limits
+                .daily_char_quota
+                .map_or("none".to_string(), |quota| format!("{} chars", quota))
+        );
+        translation_service.add_provider(provider, limits);
+    }
 
-    let libre_provider = Arc::new(LibreTranslateProvider::new());
-    translation_service.add_provider(libre_provider);
-    tracing::info!("LibreTranslate provider configured (primary)");
+    if translation_service.provider_names().is_empty() {
+        tracing::warn!("No translation providers enabled; translations will fail");
+    }
 
-    let mymemory_provider = Arc::new(MyMemoryProvider::new());
-    translation_service.add_provider(mymemory_provider);
-    tracing::info!("MyMemory provider configured (fallback 1)");
+    if let Err(e) = translation_service.load_usage().await {
+        tracing::warn!("Failed to load provider usage, quotas start from zero: {}", e);
+    }
 
-    let lingva_provider = Arc::new(LingvaProvider::new());
-    translation_service.add_provider(lingva_provider);
-    tracing::info!("Lingva provider configured (fallback 2)");
+    if config.cache.enabled {
+        let mut cache = TranslationCache::new(config.cache.capacity, config.cache.ttl);
+        if config.cache.persistent {
+            cache = cache.with_persistence(db.clone(), config.cache.persistent_ttl);
+        }
+        translation_service.set_cache(cache);
+        tracing::info!(
+            "Translation cache enabled (capacity {}, persistent: {})",
+            config.cache.capacity,
+            config.cache.persistent
+        );
+    }
 
     let translation_service = Arc::new(translation_service);
 
-    let handler = MessageHandler::new(db, translation_service);
+    let settings = Arc::new(GuildSettingsCache::new(db.clone()));
+
+    // The first tick fires right away, so stale rows also go at startup.
+    let purge_db = db.clone();
+    let purge_service = translation_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(24 * 60 * 60));
+        loop {
+            interval.tick().await;
+            purge_service.purge_cache().await;
+            ReactionHandler::purge_claims(&purge_db).await;
+        }
+    });
+
+    let channels = Arc::new(ChannelConfigCache::load(db.clone()).await?);
+    if config.channel_cache_listen {
+        channels.spawn_listener();
+    }
+
+    let (jobs, job_runner) = JobQueue::new(db.clone(), &config.jobs);
+    let handler_tasks = TaskTracker::new();
+
+    let handler = MessageHandler::new(
+        db.clone(),
+        translation_service.clone(),
+        channels.clone(),
+        settings.clone(),
+        jobs.clone(),
+        handler_tasks.clone(),
+    );
+    let interaction_handler = InteractionHandler::new(
+        db.clone(),
+        translation_service.clone(),
+        channels.clone(),
+        settings.clone(),
+        handler_tasks.clone(),
+    );
+    let reaction_handler = ReactionHandler::new(
+        db.clone(),
+        translation_service.clone(),
+        settings.clone(),
+        handler_tasks.clone(),
+    );
 
     let intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::MESSAGE_CONTENT;
 
     let mut client = Client::builder(&config.discord_token, intents)
         .event_handler(handler)
+        .event_handler(interaction_handler)
+        .event_handler(reaction_handler)
         .await?;
 
+    let worker = TranslationWorker::new(
+        db.clone(),
+        client.http.clone(),
+        translation_service,
+        channels,
+        settings,
+    );
+    let workers = job_runner.start(Arc::new(worker));
+
+    let recovery = jobs.clone();
+    tokio::spawn(async move {
+        match recovery.recover().await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Recovered {} unfinished translation jobs", count),
+            Err(e) => tracing::error!("Failed to recover translation jobs: {}", e),
+        }
+    });
+
+    let shard_manager = client.shard_manager.clone();
+
     tracing::info!("Starting Discord bot...");
 
     tokio::select! {
@@ -68,11 +170,48 @@ async fn main() -> Result<()> {
                 tracing::error!("Client error: {}", e);
             }
         }
-        _ = tokio::signal::ctrl_c() => {
-            tracing::info!("Received shutdown signal");
+        _ = shutdown::signal() => {
+            tracing::info!("Shutting down");
         }
     }
 
+    // Disconnecting first means no new messages reach the queue while it
+    // drains.
+    shard_manager.shutdown_all().await;
+    tracing::info!("Disconnected from Discord");
+
+    // Edit/delete sync, reaction and interaction translations run inside
+    // event handlers rather than the job queue; they get the same deadline.
+    handler_tasks.close();
+
+    tracing::info!(
+        "Waiting up to {}s for queued and in-flight translations",
+        config.shutdown_timeout.as_secs()
+    );
+    let (queue_drained, handlers_finished) = tokio::join!(
+        workers.shutdown(config.shutdown_timeout),
+        tokio::time::timeout(config.shutdown_timeout, handler_tasks.wait()),
+    );
+    if queue_drained {
+        tracing::info!("All queued translations finished");
+    } else {
+        tracing::warn!("Shutdown deadline passed with translations still pending");
+    }
+    if handlers_finished.is_err() {
+        tracing::warn!(
+            "Shutdown deadline passed with {} event handlers still running",
+            handler_tasks.len()
+        );
+    }
+
+    match jobs.release_claims().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Left {} unfinished translation jobs for the next start", count),
+        Err(e) => tracing::warn!("Failed to release unfinished translation jobs: {}", e),
+    }
+
+    db.close().await;
+
     tracing::info!("Bot shutdown complete");
 
     Ok(())
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CachedTranslation {
+    pub text_hash: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub source_text: String,
+    pub allow_llm: bool,
+    pub translated_text: String,
+    pub detected_language: Option<String>,
+    pub confidence: Option<f32>,
+    pub provider: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,6 +1,68 @@
+use crate::models::TranslationOutput;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// "<user> sent this: ... Which translates to this: ..." text blob.
+    Plain,
+    /// Rich embed with author, original/translation fields and a jump link.
+    Embed,
+    /// Translation only, posted under the original author's name and avatar.
+    Impersonate,
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "plain" | "text" => Some(OutputFormat::Plain),
+            "embed" | "rich" => Some(OutputFormat::Embed),
+            "impersonate" | "mirror" => Some(OutputFormat::Impersonate),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Plain => "plain",
+            OutputFormat::Embed => "embed",
+            OutputFormat::Impersonate => "impersonate",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Every message is translated from the source to the target language.
+    OneWay,
+    /// Messages in either language of the pair are translated to the other.
+    Bidirectional,
+}
+
+impl ChannelMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "oneway" | "one-way" => Some(ChannelMode::OneWay),
+            "bidirectional" | "bidi" | "both" => Some(ChannelMode::Bidirectional),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelMode::OneWay => "oneway",
+            ChannelMode::Bidirectional => "bidirectional",
+        }
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            ChannelMode::OneWay => "→",
+            ChannelMode::Bidirectional => "↔",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct TranslationChannel {
     pub id: i64,
@@ -10,6 +72,8 @@ pub struct TranslationChannel {
     pub source_language: String,
     pub target_language: String,
     pub is_active: bool,
+    pub output_format: String,
+    pub mode: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -31,8 +95,40 @@ impl TranslationChannel {
             source_language,
             target_language,
             is_active: true,
+            output_format: OutputFormat::Plain.as_str().to_string(),
+            mode: ChannelMode::OneWay.as_str().to_string(),
             created_at: now,
             updated_at: now,
         }
     }
+
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = format.as_str().to_string();
+        self
+    }
+
+    pub fn with_mode(mut self, mode: ChannelMode) -> Self {
+        self.mode = mode.as_str().to_string();
+        self
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.output_format).unwrap_or(OutputFormat::Plain)
+    }
+
+    pub fn mode(&self) -> ChannelMode {
+        ChannelMode::parse(&self.mode).unwrap_or(ChannelMode::OneWay)
+    }
+
+    /// The target language and webhook configured on the channel itself.
+    pub fn primary_output(&self) -> TranslationOutput {
+        TranslationOutput {
+            id: 0,
+            channel_id: self.channel_id,
+            target_language: self.target_language.clone(),
+            webhook_url: self.webhook_url.clone(),
+            output_format: self.output_format.clone(),
+            created_at: self.created_at,
+        }
+    }
 }
@@ -8,6 +8,8 @@ pub struct GuildSettings {
     pub default_source_lang: String,
     pub default_target_lang: String,
     pub auto_translate: bool,
+    pub llm_enabled: bool,
+    pub reaction_translation: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -21,6 +23,8 @@ impl Default for GuildSettings {
             default_source_lang: "zh".to_string(),
             default_target_lang: "en".to_string(),
             auto_translate: true,
+            llm_enabled: false,
+            reaction_translation: false,
             created_at: now,
             updated_at: now,
         }
@@ -1,3 +1,4 @@
+use crate::models::OutputFormat;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +13,12 @@ pub struct TranslationHistory {
     pub source_language: String,
     pub target_language: String,
     pub created_at: DateTime<Utc>,
+    /// Where the translation was posted, so edits and deletes of the source
+    /// message can be mirrored.
+    pub source_message_id: Option<i64>,
+    pub webhook_url: Option<String>,
+    pub output_format: String,
+    pub webhook_message_ids: Vec<i64>,
 }
 
 impl TranslationHistory {
@@ -34,6 +41,38 @@ impl TranslationHistory {
             source_language,
             target_language,
             created_at: Utc::now(),
+            source_message_id: None,
+            webhook_url: None,
+            output_format: OutputFormat::Plain.as_str().to_string(),
+            webhook_message_ids: Vec::new(),
         }
     }
+
+    pub fn with_delivery(
+        mut self,
+        source_message_id: i64,
+        webhook_url: String,
+        output_format: OutputFormat,
+        webhook_message_ids: &[u64],
+    ) -> Self {
+        self.source_message_id = Some(source_message_id);
+        self.webhook_url = Some(webhook_url);
+        self.output_format = output_format.as_str().to_string();
+        self.webhook_message_ids = webhook_message_ids.iter().map(|&id| id as i64).collect();
+        self
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.output_format).unwrap_or(OutputFormat::Plain)
+    }
+
+    /// Whether the translation is still up on a webhook, as opposed to
+    /// never delivered through one or already deleted.
+    pub fn is_posted(&self) -> bool {
+        self.webhook_url.is_some() && !self.webhook_message_ids.is_empty()
+    }
+
+    pub fn message_ids(&self) -> Vec<u64> {
+        self.webhook_message_ids.iter().map(|&id| id as u64).collect()
+    }
 }
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A message waiting to be auto-translated. It carries everything the
+/// worker needs, so it can be processed after the gateway event is gone.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TranslationJob {
+    /// Row id when the queue is persistent, `0` otherwise.
+    pub id: i64,
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub message_id: i64,
+    pub author_id: i64,
+    /// `name#discriminator`, or just the name for migrated usernames.
+    pub author_name: String,
+    /// Server nickname or global display name.
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub content: String,
+    pub jump_url: String,
+    pub sent_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TranslationJob {
+    pub fn is_persisted(&self) -> bool {
+        self.id != 0
+    }
+}
@@ -1,7 +1,15 @@
+pub mod cache;
 pub mod channel;
 pub mod guild;
 pub mod history;
+pub mod job;
+pub mod output;
+pub mod preference;
 
-pub use channel::TranslationChannel;
+pub use cache::CachedTranslation;
+pub use channel::{ChannelMode, OutputFormat, TranslationChannel};
 pub use guild::GuildSettings;
 pub use history::TranslationHistory;
+pub use job::TranslationJob;
+pub use output::TranslationOutput;
+pub use preference::UserPreference;
@@ -0,0 +1,37 @@
+use crate::models::OutputFormat;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An extra target language for a translation channel, posted to its own
+/// webhook alongside the channel's primary target.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TranslationOutput {
+    pub id: i64,
+    pub channel_id: i64,
+    pub target_language: String,
+    pub webhook_url: String,
+    pub output_format: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TranslationOutput {
+    pub fn new(channel_id: i64, target_language: String, webhook_url: String) -> Self {
+        Self {
+            id: 0,
+            channel_id,
+            target_language,
+            webhook_url,
+            output_format: OutputFormat::Plain.as_str().to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = format.as_str().to_string();
+        self
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.output_format).unwrap_or(OutputFormat::Plain)
+    }
+}
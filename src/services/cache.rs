@@ -0,0 +1,283 @@
+use crate::db::{CacheRepository, DatabasePool};
+use crate::services::translator::{TranslationRequest, TranslationResponse};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub text: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    /// Kept apart so guilds without the LLM opt-in never get its output.
+    pub allow_llm: bool,
+}
+
+impl CacheKey {
+    /// Keys on the text as sent, only trimmed at the ends: inner whitespace
+    /// such as line breaks and code block indentation is part of the
+    /// translation.
+    pub fn from_request(request: &TranslationRequest) -> Self {
+        Self {
+            text: request.text.trim().to_string(),
+            source_lang: request.source_lang.trim().to_lowercase(),
+            target_lang: request.target_lang.trim().to_lowercase(),
+            allow_llm: request.allow_llm,
+        }
+    }
+}
+
+struct CacheEntry {
+    response: TranslationResponse,
+    inserted_at: Instant,
+    tick: u64,
+}
+
+/// Least-recently-used map with a fixed capacity. Recency is tracked with a
+/// monotonically increasing tick so eviction is a pop from the ordered index.
+struct LruMap {
+    entries: HashMap<CacheKey, CacheEntry>,
+    order: BTreeMap<u64, CacheKey>,
+    next_tick: u64,
+}
+
+impl LruMap {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_tick: 0,
+        }
+    }
+
+    fn bump(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    fn get(&mut self, key: &CacheKey, ttl: Duration) -> Option<TranslationResponse> {
+        let tick = self.bump();
+        let entry = self.entries.get_mut(key)?;
+
+        if entry.inserted_at.elapsed() > ttl {
+            let old_tick = entry.tick;
+            self.entries.remove(key);
+            self.order.remove(&old_tick);
+            return None;
+        }
+
+        self.order.remove(&entry.tick);
+        entry.tick = tick;
+        self.order.insert(tick, key.clone());
+
+        Some(entry.response.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, response: TranslationResponse, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        let tick = self.bump();
+
+        if let Some(old) = self.entries.remove(&key) {
+            self.order.remove(&old.tick);
+        }
+
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+
+        self.order.insert(tick, key.clone());
+        self.entries.insert(
+            key,
+            CacheEntry {
+                response,
+                inserted_at: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub memory_hits: u64,
+    pub persistent_hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl CacheStats {
+    pub fn hits(&self) -> u64 {
+        self.memory_hits + self.persistent_hits
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits() + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits() as f64 / total as f64 * 100.0
+        }
+    }
+}
+
+/// Two-level translation cache: an in-memory LRU with TTL in front of an
+/// optional Postgres-backed `translation_cache` table.
+pub struct TranslationCache {
+    memory: Mutex<LruMap>,
+    capacity: usize,
+    ttl: Duration,
+    persistent: Option<DatabasePool>,
+    persistent_ttl: Duration,
+    memory_hits: AtomicU64,
+    persistent_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TranslationCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            memory: Mutex::new(LruMap::new()),
+            capacity,
+            ttl,
+            persistent: None,
+            persistent_ttl: Duration::ZERO,
+            memory_hits: AtomicU64::new(0),
+            persistent_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_persistence(mut self, db: DatabasePool, ttl: Duration) -> Self {
+        self.persistent = Some(db);
+        self.persistent_ttl = ttl;
+        self
+    }
+
+    pub async fn get(&self, key: &CacheKey) -> Option<TranslationResponse> {
+        if let Some(response) = self.memory.lock().unwrap().get(key, self.ttl) {
+            self.memory_hits.fetch_add(1, Ordering::Relaxed);
+            return Some(response);
+        }
+
+        if let Some(db) = &self.persistent {
+            match CacheRepository::get(
+                db.pool(),
+                &key.text,
+                &key.source_lang,
+                &key.target_lang,
+                key.allow_llm,
+                self.persistent_ttl.as_secs_f64(),
+            )
+            .await
+            {
+                Ok(Some(cached)) => {
+                    let response = TranslationResponse {
+                        translated_text: cached.translated_text,
+                        detected_language: cached.detected_language,
+                        confidence: cached.confidence,
+                        provider: cached.provider,
+                    };
+                    self.memory.lock().unwrap().insert(
+                        key.clone(),
+                        response.clone(),
+                        self.capacity,
+                    );
+                    self.persistent_hits.fetch_add(1, Ordering::Relaxed);
+                    return Some(response);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Translation cache lookup failed: {}", e);
+                }
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    pub async fn insert(&self, key: CacheKey, response: &TranslationResponse) {
+        if let Some(db) = &self.persistent {
+            if let Err(e) = CacheRepository::upsert(
+                db.pool(),
+                &key.text,
+                &key.source_lang,
+                &key.target_lang,
+                key.allow_llm,
+                &response.translated_text,
+                response.detected_language.as_deref(),
+                response.confidence,
+                response.provider.as_deref(),
+            )
+            .await
+            {
+                tracing::warn!("Translation cache write failed: {}", e);
+            }
+        }
+
+        self.memory
+            .lock()
+            .unwrap()
+            .insert(key, response.clone(), self.capacity);
+    }
+
+    pub async fn purge_expired(&self) {
+        if let Some(db) = &self.persistent {
+            match CacheRepository::purge_expired(db.pool(), self.persistent_ttl.as_secs_f64()).await
+            {
+                Ok(removed) if removed > 0 => {
+                    tracing::info!("Purged {} expired translation cache entries", removed);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Translation cache purge failed: {}", e),
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            memory_hits: self.memory_hits.load(Ordering::Relaxed),
+            persistent_hits: self.persistent_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.memory.lock().unwrap().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(text: &str, allow_llm: bool) -> CacheKey {
+        CacheKey::from_request(&TranslationRequest {
+            text: text.to_string(),
+            source_lang: "ZH".to_string(),
+            target_lang: "en".to_string(),
+            allow_llm,
+        })
+    }
+
+    #[test]
+    fn key_keeps_inner_whitespace() {
+        assert_ne!(key("a\n\nb", false), key("a b", false));
+        assert_ne!(key("```\n  x\n```", false), key("``` x ```", false));
+        assert_eq!(key("  a\nb \n", false), key("a\nb", false));
+    }
+
+    #[test]
+    fn key_separates_llm_opt_in() {
+        assert_ne!(key("你好", true), key("你好", false));
+        assert_eq!(key("你好", false).source_lang, "zh");
+    }
+}
@@ -0,0 +1,160 @@
+use crate::db::{ChannelRepository, DatabasePool, OutputRepository};
+use crate::models::{TranslationChannel, TranslationOutput};
+use anyhow::Result;
+use dashmap::DashMap;
+use serenity::all::ChannelId;
+use sqlx::postgres::PgListener;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Postgres channel the `011_channel_change_notify` triggers publish on.
+const CHANGE_CHANNEL: &str = "translation_channels_changed";
+
+/// Delay before the first attempt to restart a failed listener, doubling up
+/// to `LISTENER_MAX_BACKOFF`.
+const LISTENER_MIN_BACKOFF: Duration = Duration::from_secs(1);
+const LISTENER_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// An active translation channel together with its extra outputs.
+#[derive(Debug, Clone)]
+pub struct CachedChannel {
+    pub config: TranslationChannel,
+    pub outputs: Vec<TranslationOutput>,
+}
+
+/// Every active translation channel, kept in memory so messages in
+/// unconfigured channels never reach the database. The whole set is loaded
+/// at startup; commands that change a channel or its outputs must call
+/// `refresh` afterwards.
+pub struct ChannelConfigCache {
+    db: DatabasePool,
+    entries: DashMap<i64, CachedChannel>,
+}
+
+impl ChannelConfigCache {
+    pub async fn load(db: DatabasePool) -> Result<Self> {
+        let cache = Self {
+            db,
+            entries: DashMap::new(),
+        };
+        cache.reload().await?;
+
+        Ok(cache)
+    }
+
+    pub fn get(&self, channel_id: ChannelId) -> Option<CachedChannel> {
+        self.entries
+            .get(&(channel_id.get() as i64))
+            .map(|entry| entry.clone())
+    }
+
+    /// Replaces the whole cache with the current database contents.
+    pub async fn reload(&self) -> Result<()> {
+        let channels = ChannelRepository::get_all_active(self.db.pool()).await?;
+
+        let mut outputs: HashMap<i64, Vec<TranslationOutput>> = HashMap::new();
+        for output in OutputRepository::get_all_active(self.db.pool()).await? {
+            outputs.entry(output.channel_id).or_default().push(output);
+        }
+
+        self.entries.retain(|channel_id, _| {
+            channels
+                .iter()
+                .any(|config| config.channel_id == *channel_id)
+        });
+
+        for config in channels {
+            let outputs = outputs.remove(&config.channel_id).unwrap_or_default();
+            self.entries
+                .insert(config.channel_id, CachedChannel { config, outputs });
+        }
+
+        tracing::info!("Loaded {} translation channel configs", self.entries.len());
+
+        Ok(())
+    }
+
+    /// Re-reads one channel, dropping it when it is no longer active. A
+    /// failed read keeps the previous entry rather than silently disabling
+    /// the channel.
+    pub async fn refresh(&self, channel_id: i64) {
+        let pool = self.db.pool();
+
+        let loaded = match ChannelRepository::get_by_channel_id(pool, channel_id).await {
+            Ok(Some(config)) => OutputRepository::get_by_channel(pool, channel_id)
+                .await
+                .map(|outputs| Some(CachedChannel { config, outputs })),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        match loaded {
+            Ok(Some(entry)) => {
+                self.entries.insert(channel_id, entry);
+            }
+            Ok(None) => {
+                self.entries.remove(&channel_id);
+            }
+            Err(e) => tracing::warn!("Failed to refresh channel config {}: {}", channel_id, e),
+        }
+    }
+
+    /// Follows changes made by other bot instances for as long as the
+    /// process runs, reconnecting with exponential backoff when the listener
+    /// fails.
+    pub fn spawn_listener(self: &Arc<Self>) {
+        let cache = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut delay = LISTENER_MIN_BACKOFF;
+            let mut reconnecting = false;
+
+            loop {
+                if let Err(e) = cache.listen(reconnecting, &mut delay).await {
+                    tracing::error!(
+                        "Channel config listener failed, retrying in {:?}: {}",
+                        delay,
+                        e
+                    );
+                }
+
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(LISTENER_MAX_BACKOFF);
+                reconnecting = true;
+            }
+        });
+    }
+
+    /// Runs until the listener fails. After a reconnect the whole cache is
+    /// reloaded, since notifications sent while disconnected were lost.
+    async fn listen(&self, reconnecting: bool, delay: &mut Duration) -> Result<()> {
+        let mut listener = PgListener::connect_with(self.db.pool()).await?;
+        listener.listen(CHANGE_CHANNEL).await?;
+        tracing::info!("Listening for channel config changes");
+
+        if reconnecting {
+            self.reload().await?;
+        }
+        *delay = LISTENER_MIN_BACKOFF;
+
+        loop {
+            match listener.try_recv().await? {
+                Some(notification) => match notification.payload().parse() {
+                    Ok(channel_id) => self.refresh(channel_id).await,
+                    Err(_) => tracing::warn!(
+                        "Ignoring malformed channel change notification: {}",
+                        notification.payload()
+                    ),
+                },
+                // The connection dropped and will be re-established on the
+                // next call; anything announced in between was missed.
+                None => {
+                    if let Err(e) = self.reload().await {
+                        tracing::warn!("Failed to reload channel configs: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+/// Maximum length of a Discord message `content`, in characters.
+pub const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Bytes,
+    Chars,
+    /// Bytes once percent-encoded for a URL, where everything but unreserved
+    /// ASCII takes three.
+    UrlEncoded,
+}
+
+impl Unit {
+    pub fn len(&self, text: &str) -> usize {
+        match self {
+            Unit::Bytes => text.len(),
+            Unit::Chars => text.chars().count(),
+            Unit::UrlEncoded => text
+                .bytes()
+                .map(|b| {
+                    if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+                        1
+                    } else {
+                        3
+                    }
+                })
+                .sum(),
+        }
+    }
+}
+
+/// Splits `text` into pieces no longer than `max_len`, preferring sentence
+/// boundaries, then whitespace, then arbitrary character boundaries. Each
+/// piece keeps its trailing whitespace so concatenating them restores `text`.
+pub fn split_text(text: &str, max_len: usize, unit: Unit) -> Vec<String> {
+    if unit.len(text) <= max_len || max_len == 0 {
+        return vec![text.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in sentences(text) {
+        if unit.len(&current) + unit.len(sentence) <= max_len {
+            current.push_str(sentence);
+            continue;
+        }
+
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+
+        if unit.len(sentence) <= max_len {
+            current.push_str(sentence);
+            continue;
+        }
+
+        for word in words(sentence) {
+            if unit.len(&current) + unit.len(word) <= max_len {
+                current.push_str(word);
+                continue;
+            }
+
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+
+            if unit.len(word) <= max_len {
+                current.push_str(word);
+                continue;
+            }
+
+            for c in word.chars() {
+                if unit.len(&current) + unit.len(c.encode_utf8(&mut [0; 4])) > max_len {
+                    chunks.push(std::mem::take(&mut current));
+                }
+                current.push(c);
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+/// Splits an outgoing Discord message into pieces that fit the message limit.
+/// A piece that ends inside a fenced code block is closed and the fence is
+/// reopened at the start of the next piece so formatting carries over.
+pub fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    const FENCE: &str = "```";
+    const MAX_LANGUAGE_TAG: usize = 16;
+    // Room for a reopened fence with its language tag and a closing fence.
+    let budget = max_chars
+        .saturating_sub(2 * (FENCE.len() + 1) + MAX_LANGUAGE_TAG)
+        .max(1);
+
+    let mut pieces = Vec::new();
+    let mut reopen: Option<String> = None;
+
+    for chunk in split_text(text, budget, Unit::Chars) {
+        let mut piece = reopen.take().unwrap_or_default();
+        piece.push_str(&chunk);
+
+        if let Some(language) = open_fence(&piece) {
+            if !piece.ends_with('\n') {
+                piece.push('\n');
+            }
+            piece.push_str(FENCE);
+            let language: String = language.chars().take(MAX_LANGUAGE_TAG).collect();
+            reopen = Some(format!("{}{}\n", FENCE, language));
+        }
+
+        // A reopened fence can push a piece over the limit; fall back to a
+        // hard split rather than letting Discord reject it.
+        if piece.chars().count() > max_chars {
+            pieces.extend(split_text(&piece, max_chars, Unit::Chars));
+        } else {
+            pieces.push(piece);
+        }
+    }
+
+    pieces
+        .into_iter()
+        .filter(|piece| !piece.trim().is_empty())
+        .collect()
+}
+
+/// Returns the language tag of a fenced block left open at the end of
+/// `text`, if any.
+fn open_fence(text: &str) -> Option<String> {
+    let mut open: Option<String> = None;
+    let mut rest = text;
+
+    while let Some(index) = rest.find("```") {
+        let after = &rest[index + 3..];
+        open = match open {
+            Some(_) => None,
+            None => Some(
+                after
+                    .split(|c: char| c.is_whitespace())
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+        };
+        rest = after;
+    }
+
+    open
+}
+
+/// Yields sentences including their terminator and trailing whitespace.
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut end = rest.len();
+        let mut chars = rest.char_indices().peekable();
+
+        while let Some((index, c)) = chars.next() {
+            if matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '…' | '\n') {
+                let mut stop = index + c.len_utf8();
+                while let Some((next_index, next)) = chars.peek().copied() {
+                    if next.is_whitespace()
+                        || matches!(
+                            next,
+                            '.' | '!' | '?' | '。' | '！' | '？' | '」' | '"' | ')'
+                        )
+                    {
+                        stop = next_index + next.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                // Only ASCII terminators need a following space to count;
+                // CJK punctuation ends a sentence on its own.
+                let cjk = matches!(c, '。' | '！' | '？' | '\n');
+                if cjk || stop == rest.len() || rest[..stop].ends_with(char::is_whitespace) {
+                    end = stop;
+                    break;
+                }
+            }
+        }
+
+        let (sentence, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(sentence)
+    })
+}
+
+/// Yields words including their trailing whitespace.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let end = rest[word_end..]
+            .find(|c: char| !c.is_whitespace())
+            .map(|offset| word_end + offset)
+            .unwrap_or(rest.len());
+
+        let (word, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_left_alone() {
+        assert_eq!(split_text("你好。", 100, Unit::Bytes), vec!["你好。"]);
+    }
+
+    #[test]
+    fn byte_limit_holds_for_multibyte_text() {
+        let text = "今天天气很好。我们去公园散步吧！晚上一起吃饭好吗？".repeat(4);
+        let chunks = split_text(&text, 40, Unit::Bytes);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 40));
+        assert_eq!(chunks.concat(), text);
+        // Every sentence fits within the limit, so none is cut mid-way.
+        assert!(chunks.iter().all(|chunk| chunk.ends_with(['。', '！', '？'])));
+    }
+
+    #[test]
+    fn url_encoded_limit_counts_escapes() {
+        assert_eq!(Unit::UrlEncoded.len("abc-_.~"), 7);
+        assert_eq!(Unit::UrlEncoded.len("a b"), 5);
+        assert_eq!(Unit::UrlEncoded.len("你"), 9);
+
+        let text = "你好世界。".repeat(10);
+        let chunks = split_text(&text, 60, Unit::UrlEncoded);
+        assert!(chunks.iter().all(|chunk| Unit::UrlEncoded.len(chunk) <= 60));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn oversized_sentence_falls_back_to_words_then_characters() {
+        let text = "one two three four five six seven eight nine ten.";
+        let chunks = split_text(text, 12, Unit::Chars);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 12));
+        assert_eq!(chunks.concat(), text);
+        assert_eq!(chunks[0], "one two ");
+
+        let unbroken = "好".repeat(25);
+        let chunks = split_text(&unbroken, 10, Unit::Chars);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), unbroken);
+    }
+
+    #[test]
+    fn sentences_keep_terminators_and_spacing() {
+        let parsed: Vec<&str> = sentences("Hi there. 你好！How are you?!  Fine").collect();
+        assert_eq!(parsed, vec!["Hi there. ", "你好！", "How are you?!  ", "Fine"]);
+
+        // A dot without a following space, e.g. in a version, does not end a
+        // sentence.
+        let parsed: Vec<&str> = sentences("Use v1.2 now. Ok").collect();
+        assert_eq!(parsed, vec!["Use v1.2 now. ", "Ok"]);
+    }
+
+    #[test]
+    fn split_message_reopens_code_fences() {
+        let code: String = (0..40).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let text = format!("Here:\n```rust\n{}```\nDone.", code);
+        let pieces = split_message(&text, 200);
+
+        assert!(pieces.len() > 1);
+        for piece in &pieces {
+            assert!(piece.chars().count() <= 200);
+            assert_eq!(piece.matches("```").count() % 2, 0, "unbalanced: {}", piece);
+        }
+        assert!(pieces[1].starts_with("```rust\n"));
+    }
+
+    #[test]
+    fn split_message_drops_blank_pieces() {
+        let text = format!("{}\n\n\n\n{}", "a".repeat(10), "b".repeat(10));
+        let pieces = split_message(&text, 60);
+
+        assert!(pieces.iter().all(|piece| !piece.trim().is_empty()));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::services::translator::{
+    http_client, TranslationProvider, TranslationRequest, TranslationResponse,
+};
+use crate::utils::config::ProviderConfig;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const SUPPORTED_LANGUAGES: &[&str] = &[
+    "ar", "bg", "cs", "da", "de", "el", "en", "es", "et", "fi", "fr", "hu", "id", "it", "ja", "ko",
+    "lt", "lv", "nb", "nl", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "tr", "uk", "zh",
+];
+
+pub struct DeepLProvider {
+    base_url: String,
+    auth_key: String,
+    formality: Option<String>,
+    client: reqwest::Client,
+}
+
+impl DeepLProvider {
+    pub const FREE_URL: &'static str = "https://api-free.deepl.com";
+    pub const PRO_URL: &'static str = "https://api.deepl.com";
+
+    pub fn from_config(config: &ProviderConfig) -> Result<Self> {
+        let auth_key = config
+            .api_key
+            .clone()
+            .ok_or_else(|| anyhow!("DeepL provider requires DEEPL_API_KEY"))?;
+
+        // DeepL issues free-plan keys with a ":fx" suffix and only accepts
+        // them on the api-free host.
+        let base_url = match &config.base_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None if auth_key.ends_with(":fx") => Self::FREE_URL.to_string(),
+            None => Self::PRO_URL.to_string(),
+        };
+
+        let formality = config
+            .formality
+            .as_deref()
+            .map(Self::normalize_formality)
+            .transpose()?;
+
+        Ok(Self {
+            base_url,
+            auth_key,
+            formality,
+            client: http_client(config.timeout()),
+        })
+    }
+
+    /// Uses the `prefer_*` variants so that target languages without
+    /// formality support fall back to the default instead of erroring.
+    fn normalize_formality(value: &str) -> Result<String> {
+        match value.to_lowercase().as_str() {
+            "default" => Ok("default".to_string()),
+            "more" | "formal" | "prefer_more" => Ok("prefer_more".to_string()),
+            "less" | "informal" | "prefer_less" => Ok("prefer_less".to_string()),
+            other => Err(anyhow!("Invalid DeepL formality: {}", other)),
+        }
+    }
+
+    fn base_lang(lang: &str) -> String {
+        let lower = lang.to_lowercase();
+        match lower.as_str() {
+            "chinese" => "zh".to_string(),
+            "english" => "en".to_string(),
+            "japanese" => "ja".to_string(),
+            "korean" => "ko".to_string(),
+            "german" => "de".to_string(),
+            "portuguese" => "pt".to_string(),
+            "no" | "nn" => "nb".to_string(),
+            _ => lower
+                .split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    fn source_lang_code(&self, lang: &str) -> Option<String> {
+        match lang.to_lowercase().as_str() {
+            "auto" | "" => None,
+            _ => Some(Self::base_lang(lang).to_uppercase()),
+        }
+    }
+
+    fn target_lang_code(&self, lang: &str) -> String {
+        match lang.to_lowercase().as_str() {
+            "en-gb" => "EN-GB".to_string(),
+            "en" | "en-us" | "english" => "EN-US".to_string(),
+            "pt-pt" => "PT-PT".to_string(),
+            "pt" | "pt-br" | "portuguese" => "PT-BR".to_string(),
+            "zh-tw" | "zh-hant" => "ZH-HANT".to_string(),
+            "zh" | "zh-cn" | "zh-hans" | "chinese" => "ZH-HANS".to_string(),
+            _ => Self::base_lang(lang).to_uppercase(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DeepLResponse {
+    translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Deserialize)]
+struct DeepLTranslation {
+    detected_source_language: Option<String>,
+    text: String,
+}
+
+#[async_trait]
+impl TranslationProvider for DeepLProvider {
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResponse> {
+        let url = format!("{}/v2/translate", self.base_url);
+
+        let mut payload = serde_json::json!({
+            "text": [request.text],
+            "target_lang": self.target_lang_code(&request.target_lang),
+        });
+
+        if let Some(source_lang) = self.source_lang_code(&request.source_lang) {
+            payload["source_lang"] = serde_json::Value::String(source_lang);
+        }
+
+        if let Some(formality) = &self.formality {
+            payload["formality"] = serde_json::Value::String(formality.clone());
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.auth_key))
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let api_response: DeepLResponse = response.json().await?;
+
+        let translation = api_response
+            .translations
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("DeepL returned no translations"))?;
+
+        Ok(TranslationResponse {
+            translated_text: translation.text,
+            detected_language: translation
+                .detected_source_language
+                .map(|lang| lang.to_lowercase()),
+            confidence: None,
+            provider: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        "DeepL"
+    }
+
+    fn supports_language(&self, lang: &str) -> bool {
+        lang.eq_ignore_ascii_case("auto")
+            || SUPPORTED_LANGUAGES.contains(&Self::base_lang(lang).as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Answers `requests` connections with a canned DeepL reply and hands
+    /// back each raw request.
+    fn stand_in(requests: usize) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut content_length = 0;
+
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+
+                let reply = r#"{"translations":[{"detected_source_language":"ZH","text":"Hello"}]}"#;
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                )
+                .unwrap();
+                tx.send(request).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn body_of(request: &str) -> serde_json::Value {
+        let (_, body) = request.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_mapped_codes_to_the_configured_url() {
+        let (url, requests) = stand_in(2);
+        // A free-plan key would otherwise select the api-free host.
+        let config: ProviderConfig = serde_json::from_value(serde_json::json!({
+            "kind": "deepl",
+            "base_url": format!("{}/", url),
+            "api_key": "secret:fx",
+            "formality": "formal",
+        }))
+        .unwrap();
+        let provider = DeepLProvider::from_config(&config).unwrap();
+
+        let response = provider
+            .translate(&TranslationRequest {
+                text: "你好".to_string(),
+                source_lang: "zh-TW".to_string(),
+                target_lang: "en".to_string(),
+                allow_llm: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.translated_text, "Hello");
+        assert_eq!(response.detected_language.as_deref(), Some("zh"));
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /v2/translate HTTP/1.1\r\n"));
+        assert!(request
+            .to_lowercase()
+            .contains("authorization: deepl-auth-key secret:fx\r\n"));
+        assert_eq!(
+            body_of(&request),
+            serde_json::json!({
+                "text": ["你好"],
+                "source_lang": "ZH",
+                "target_lang": "EN-US",
+                "formality": "prefer_more",
+            })
+        );
+
+        provider
+            .translate(&TranslationRequest {
+                text: "Hello".to_string(),
+                source_lang: "auto".to_string(),
+                target_lang: "zh-TW".to_string(),
+                allow_llm: false,
+            })
+            .await
+            .unwrap();
+
+        let body = body_of(&requests.recv().unwrap());
+        assert_eq!(body["target_lang"], "ZH-HANT");
+        assert!(body.get("source_lang").is_none());
+    }
+}
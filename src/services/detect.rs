@@ -0,0 +1,242 @@
+use crate::services::languages;
+
+/// Result of a language detection, either from a provider or the local
+/// script heuristic. `confidence` is normalised to 0.0–1.0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub language: String,
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Latin,
+    Arabic,
+    Devanagari,
+    Thai,
+    Greek,
+    Hebrew,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        match c as u32 {
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Some(Script::Kana),
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Some(Script::Hangul),
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F => {
+                Some(Script::Han)
+            }
+            0x0400..=0x052F => Some(Script::Cyrillic),
+            0x0600..=0x06FF | 0x0750..=0x077F => Some(Script::Arabic),
+            0x0900..=0x097F => Some(Script::Devanagari),
+            0x0E00..=0x0E7F => Some(Script::Thai),
+            0x0370..=0x03FF => Some(Script::Greek),
+            0x0590..=0x05FF => Some(Script::Hebrew),
+            _ if c.is_ascii_alphabetic() => Some(Script::Latin),
+            0x00C0..=0x024F => Some(Script::Latin),
+            _ => None,
+        }
+    }
+
+    /// The language a script implies on its own, if it is unambiguous enough
+    /// to skip a provider round-trip. Latin is shared by too many languages.
+    fn language(&self) -> Option<&'static str> {
+        match self {
+            Script::Han => Some("zh"),
+            Script::Kana => Some("ja"),
+            Script::Hangul => Some("ko"),
+            Script::Cyrillic => Some("ru"),
+            Script::Arabic => Some("ar"),
+            Script::Devanagari => Some("hi"),
+            Script::Thai => Some("th"),
+            Script::Greek => Some("el"),
+            Script::Hebrew => Some("he"),
+            Script::Latin => None,
+        }
+    }
+}
+
+/// Share of letters a script needs before its language is trusted without
+/// asking a provider.
+pub const CONFIDENT_SHARE: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptGuess {
+    pub script: Script,
+    /// Share of letters belonging to `script`.
+    pub share: f32,
+}
+
+impl ScriptGuess {
+    pub fn detection(&self) -> Option<Detection> {
+        self.script.language().map(|language| Detection {
+            language: language.to_string(),
+            confidence: Some(self.share),
+        })
+    }
+
+    /// Like `detection`, but only when the script dominates the text enough
+    /// to skip a provider round-trip.
+    pub fn confident_detection(&self) -> Option<Detection> {
+        self.detection().filter(|_| self.share >= CONFIDENT_SHARE)
+    }
+}
+
+/// Classifies text by the dominant writing system. Japanese text mixes kanji
+/// with kana, so any meaningful amount of kana wins over Han.
+pub fn guess_script(text: &str) -> Option<ScriptGuess> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    let mut total = 0usize;
+
+    for script in text.chars().filter_map(Script::of) {
+        total += 1;
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+
+    if total == 0 {
+        return None;
+    }
+
+    let count_of = |script: Script| {
+        counts
+            .iter()
+            .find(|(s, _)| *s == script)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    };
+
+    let kana = count_of(Script::Kana);
+    let han = count_of(Script::Han);
+    if kana > 0 && kana * 10 >= (kana + han) {
+        return Some(ScriptGuess {
+            script: Script::Kana,
+            share: (kana + han) as f32 / total as f32,
+        });
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(script, count)| ScriptGuess {
+            script,
+            share: count as f32 / total as f32,
+        })
+}
+
+/// Reduces a language code to its primary subtag, e.g. `zh-TW` to `zh`.
+pub fn base_language(code: &str) -> String {
+    code.trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Whether two codes name the same language. Registered codes and aliases
+/// compare by their registry entry, so `zh-hans` matches `zh` but `zh-TW`
+/// does not. A bare code such as `en` also matches unregistered regional
+/// forms like `en-US`.
+pub fn same_language(a: &str, b: &str) -> bool {
+    let (a, registered_a) = normalize_language(a);
+    let (b, registered_b) = normalize_language(b);
+
+    if a == b {
+        return true;
+    }
+
+    let has_subtag = |code: &str| code.contains('-');
+    if registered_a && registered_b || has_subtag(&a) && has_subtag(&b) {
+        return false;
+    }
+
+    base_language(&a) == base_language(&b)
+}
+
+/// Lowercased code with `-` separators, mapped to its registry code when
+/// there is one. The flag tells whether it was registered.
+fn normalize_language(code: &str) -> (String, bool) {
+    let code = code.trim().replace('_', "-").to_lowercase();
+
+    match languages::lookup(&code) {
+        Some(language) => (language.code.to_lowercase(), true),
+        None => (code, false),
+    }
+}
+
+pub fn is_auto(code: &str) -> bool {
+    code.eq_ignore_ascii_case("auto")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_language_keeps_registered_variants_apart() {
+        let cases = [
+            ("zh", "zh", true),
+            ("ZH", "zh-hans", true),
+            ("zh-CN", "zh", true),
+            ("zh_hant", "zh-TW", true),
+            ("zh", "zh-TW", false),
+            ("zh-CN", "zh-TW", false),
+            ("en", "en-US", true),
+            ("en-GB", "en-US", true),
+            ("sr-Latn", "sr-Cyrl", false),
+            ("sr", "sr-Latn", true),
+            ("pt-BR", "pt", true),
+            ("en", "de", false),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(same_language(a, b), expected, "{} vs {}", a, b);
+            assert_eq!(same_language(b, a), expected, "{} vs {}", b, a);
+        }
+    }
+
+    #[test]
+    fn guess_script_picks_the_dominant_script() {
+        assert_eq!(guess_script(""), None);
+        assert_eq!(guess_script("123 !?"), None);
+
+        let guess = guess_script("你").unwrap();
+        assert_eq!(guess.script, Script::Han);
+        assert_eq!(guess.share, 1.0);
+
+        let guess = guess_script("привет world").unwrap();
+        assert_eq!(guess.script, Script::Cyrillic);
+        assert!((guess.share - 6.0 / 11.0).abs() < f32::EPSILON);
+
+        // Kanji with a little kana is still Japanese.
+        let guess = guess_script("日本語を勉強する").unwrap();
+        assert_eq!(guess.script, Script::Kana);
+        assert_eq!(guess.detection().unwrap().language, "ja");
+    }
+
+    #[test]
+    fn confident_detection_needs_a_clear_majority() {
+        // Exactly at the threshold: 3 of 5 letters are Han.
+        let guess = guess_script("你好吗 ok").unwrap();
+        assert_eq!(guess.share, CONFIDENT_SHARE);
+        assert_eq!(guess.confident_detection().unwrap().language, "zh");
+
+        // Just below it: 5 of 9 letters are Han.
+        let guess = guess_script("你好吗你好 okay").unwrap();
+        assert_eq!(guess.script, Script::Han);
+        assert!(guess.share < CONFIDENT_SHARE);
+        assert_eq!(guess.confident_detection(), None);
+        assert!(guess.detection().is_some());
+
+        // Latin never implies a language on its own.
+        let guess = guess_script("hello").unwrap();
+        assert_eq!(guess.share, 1.0);
+        assert_eq!(guess.confident_detection(), None);
+    }
+}
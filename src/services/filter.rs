@@ -0,0 +1,243 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// No text at all, e.g. attachment- or sticker-only messages.
+    Empty,
+    EmojiOnly,
+    UrlOnly,
+    CodeOnly,
+    MentionOnly,
+    /// Only punctuation, digits or other symbols remain.
+    PunctuationOnly,
+    AlreadyTargetLanguage,
+    /// Dropped because the translation queue was full.
+    QueueFull,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Empty => "empty",
+            SkipReason::EmojiOnly => "emoji_only",
+            SkipReason::UrlOnly => "url_only",
+            SkipReason::CodeOnly => "code_only",
+            SkipReason::MentionOnly => "mention_only",
+            SkipReason::PunctuationOnly => "punctuation_only",
+            SkipReason::AlreadyTargetLanguage => "already_target_language",
+            SkipReason::QueueFull => "queue_full",
+        }
+    }
+
+    /// Human-readable label for a stored reason string.
+    pub fn label(reason: &str) -> &str {
+        match reason {
+            "empty" => "No text (attachments/stickers)",
+            "emoji_only" => "Emoji only",
+            "url_only" => "Links only",
+            "code_only" => "Code only",
+            "mention_only" => "Mentions only",
+            "punctuation_only" => "Punctuation/numbers only",
+            "already_target_language" => "Already in target language",
+            "queue_full" => "Dropped while the queue was full",
+            other => other,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stripped {
+    remaining: String,
+    had_code: bool,
+    had_url: bool,
+    had_emoji: bool,
+    had_mention: bool,
+}
+
+/// Returns why a message has nothing worth sending to a provider, or `None`
+/// when it contains translatable text.
+pub fn classify(content: &str) -> Option<SkipReason> {
+    if content.trim().is_empty() {
+        return Some(SkipReason::Empty);
+    }
+
+    let stripped = strip_untranslatable(content);
+
+    if stripped.remaining.chars().any(char::is_alphabetic) {
+        return None;
+    }
+
+    let has_symbols = stripped
+        .remaining
+        .chars()
+        .any(|c| !c.is_whitespace() && !is_emoji_char(c));
+    let has_unicode_emoji = stripped.remaining.chars().any(is_emoji_char);
+
+    if has_symbols {
+        Some(SkipReason::PunctuationOnly)
+    } else if stripped.had_code {
+        Some(SkipReason::CodeOnly)
+    } else if stripped.had_url {
+        Some(SkipReason::UrlOnly)
+    } else if stripped.had_emoji || has_unicode_emoji {
+        Some(SkipReason::EmojiOnly)
+    } else if stripped.had_mention {
+        Some(SkipReason::MentionOnly)
+    } else {
+        Some(SkipReason::Empty)
+    }
+}
+
+fn strip_untranslatable(content: &str) -> Stripped {
+    let mut out = Stripped::default();
+    let mut rest = content;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("```") {
+            out.had_code = true;
+            rest = match after.find("```") {
+                Some(end) => &after[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = after.find('`') {
+                out.had_code = true;
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        if rest.starts_with('<') {
+            if let Some(end) = rest.find('>') {
+                let inner = &rest[1..end];
+                if inner.starts_with(':') || inner.starts_with("a:") {
+                    out.had_emoji = true;
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                // Users, roles, channels, timestamps and slash commands
+                // (`</name:id>`).
+                if inner.starts_with('@')
+                    || inner.starts_with('#')
+                    || inner.starts_with("t:")
+                    || inner.starts_with('/')
+                {
+                    out.had_mention = true;
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if inner.starts_with("http://") || inner.starts_with("https://") {
+                    out.had_url = true;
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            out.had_url = true;
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = &rest[end..];
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix(":") {
+            // Unicode emoji shortcodes such as :thumbsup: as typed by bridges.
+            if let Some(end) = after.find(':') {
+                let name = &after[..end];
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-')
+                {
+                    out.had_emoji = true;
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        let mut chars = rest.chars();
+        if let Some(c) = chars.next() {
+            out.remaining.push(c);
+        }
+        rest = chars.as_str();
+    }
+
+    out
+}
+
+pub(crate) fn is_emoji_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x2300..=0x23FF
+            | 0xFE00..=0xFE0F
+            | 0x200D
+            | 0x20E3
+            | 0x3030
+            | 0x303D
+            | 0x3297
+            | 0x3299
+            | 0xE0020..=0xE007F
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_untranslatable_messages() {
+        let cases = [
+            ("", Some(SkipReason::Empty)),
+            ("   \n", Some(SkipReason::Empty)),
+            ("😀🎉", Some(SkipReason::EmojiOnly)),
+            ("<:pepe:123456> <a:dance:654321>", Some(SkipReason::EmojiOnly)),
+            (":thumbsup: :+1:", Some(SkipReason::EmojiOnly)),
+            ("https://example.com/a?b=c", Some(SkipReason::UrlOnly)),
+            ("<https://example.com>", Some(SkipReason::UrlOnly)),
+            ("```rust\nfn main() {}\n```", Some(SkipReason::CodeOnly)),
+            ("`cargo build`", Some(SkipReason::CodeOnly)),
+            ("<@123> <@!456> <@&789> <#111>", Some(SkipReason::MentionOnly)),
+            ("<t:1700000000:R>", Some(SkipReason::MentionOnly)),
+            ("</translate:1234567890>", Some(SkipReason::MentionOnly)),
+            ("</config set:1234567890>", Some(SkipReason::MentionOnly)),
+            ("123 + 456 = 579!", Some(SkipReason::PunctuationOnly)),
+            ("<@123> ???", Some(SkipReason::PunctuationOnly)),
+        ];
+
+        for (content, expected) in cases {
+            assert_eq!(classify(content), expected, "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn keeps_messages_with_text() {
+        let cases = [
+            "你好",
+            "hello <@123>",
+            "see https://example.com for details",
+            "run `cargo build` first",
+            "</translate:1234567890> 怎么用？",
+            "😀 nice",
+            "unclosed `backtick",
+            "a:b: c",
+        ];
+
+        for content in cases {
+            assert_eq!(classify(content), None, "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn strips_markup_but_keeps_surrounding_text() {
+        let stripped = strip_untranslatable("hi <@123>, see <:wave:1> `x` https://a.b ok");
+
+        assert_eq!(stripped.remaining, "hi , see    ok");
+        assert!(stripped.had_mention && stripped.had_emoji && stripped.had_code && stripped.had_url);
+    }
+}
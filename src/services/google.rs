@@ -0,0 +1,464 @@
+use crate::services::detect::Detection;
+use crate::services::translator::{
+    http_client, TranslationProvider, TranslationRequest, TranslationResponse,
+};
+use crate::utils::config::ProviderConfig;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const NO_TRANSLATE_OPEN: &str = "<span translate=\"no\">";
+const NO_TRANSLATE_CLOSE: &str = "</span>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiVersion {
+    V2,
+    V3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextFormat {
+    Text,
+    Html,
+}
+
+pub struct GoogleTranslateProvider {
+    base_url: String,
+    api_key: String,
+    version: ApiVersion,
+    project_id: Option<String>,
+    format: TextFormat,
+    client: reqwest::Client,
+}
+
+impl GoogleTranslateProvider {
+    pub const DEFAULT_URL: &'static str = "https://translation.googleapis.com";
+
+    pub fn from_config(config: &ProviderConfig) -> Result<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .ok_or_else(|| anyhow!("Google provider requires GOOGLE_API_KEY"))?;
+
+        let version = match config
+            .api_version
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            None | Some("v2") => ApiVersion::V2,
+            Some("v3") => ApiVersion::V3,
+            Some(other) => return Err(anyhow!("Unsupported Google API version: {}", other)),
+        };
+
+        if version == ApiVersion::V3 && config.project_id.is_none() {
+            return Err(anyhow!("Google v3 API requires GOOGLE_PROJECT_ID"));
+        }
+
+        let format = match config.format.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("text") => TextFormat::Text,
+            Some("html") => TextFormat::Html,
+            Some(other) => return Err(anyhow!("Unsupported Google text format: {}", other)),
+        };
+
+        Ok(Self {
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            version,
+            project_id: config.project_id.clone(),
+            format,
+            client: http_client(config.timeout()),
+        })
+    }
+
+    fn normalize_lang_code(&self, lang: &str) -> Option<String> {
+        match lang.to_lowercase().as_str() {
+            "auto" | "" => None,
+            "zh" | "zh-cn" | "zh-hans" | "chinese" => Some("zh-CN".to_string()),
+            "zh-tw" | "zh-hant" => Some("zh-TW".to_string()),
+            "english" => Some("en".to_string()),
+            "japanese" => Some("ja".to_string()),
+            "korean" => Some("ko".to_string()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn v3_url(&self, method: &str) -> String {
+        format!(
+            "{}/v3/projects/{}/locations/global:{}",
+            self.base_url,
+            self.project_id.as_deref().unwrap_or_default(),
+            method
+        )
+    }
+
+    fn mime_type(format: TextFormat) -> &'static str {
+        match format {
+            TextFormat::Text => "text/plain",
+            TextFormat::Html => "text/html",
+        }
+    }
+
+    /// Format for one request. In HTML mode user text is escaped so it is
+    /// never parsed as markup, and markup placeholders are wrapped in
+    /// `<span translate="no">` so Google leaves them alone. Text without
+    /// placeholders has nothing to protect and goes out as plain text.
+    fn prepare_text(&self, text: &str) -> (String, TextFormat) {
+        if self.format == TextFormat::Text || !text.contains("[[") {
+            return (text.to_string(), TextFormat::Text);
+        }
+
+        let escaped = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+
+        let mut prepared = String::with_capacity(escaped.len());
+        let mut rest = escaped.as_str();
+
+        while let Some(start) = rest.find("[[") {
+            let (before, candidate) = rest.split_at(start);
+            prepared.push_str(before);
+
+            let placeholder_len = candidate[2..]
+                .find("]]")
+                .filter(|&end| {
+                    end > 0 && candidate[2..2 + end].bytes().all(|b| b.is_ascii_digit())
+                })
+                .map(|end| end + 4);
+
+            match placeholder_len {
+                Some(len) => {
+                    prepared.push_str(NO_TRANSLATE_OPEN);
+                    prepared.push_str(&candidate[..len]);
+                    prepared.push_str(NO_TRANSLATE_CLOSE);
+                    rest = &candidate[len..];
+                }
+                None => {
+                    prepared.push_str("[[");
+                    rest = &candidate[2..];
+                }
+            }
+        }
+        prepared.push_str(rest);
+
+        (prepared, TextFormat::Html)
+    }
+
+    /// Undoes `prepare_text` on the result: drops the no-translate spans and
+    /// decodes the entities Google returns in HTML mode.
+    fn finish_text(&self, text: String, format: TextFormat) -> String {
+        match format {
+            TextFormat::Text => text,
+            TextFormat::Html => decode_html_entities(
+                &text
+                    .replace(NO_TRANSLATE_OPEN, "")
+                    .replace(NO_TRANSLATE_CLOSE, ""),
+            ),
+        }
+    }
+
+    async fn detect_language(&self, text: &str) -> Result<(String, f32)> {
+        match self.version {
+            ApiVersion::V2 => {
+                let url = format!("{}/language/translate/v2/detect", self.base_url);
+                let response = self
+                    .client
+                    .post(&url)
+                    .query(&[("key", &self.api_key)])
+                    .json(&serde_json::json!({ "q": text }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let api_response: V2DetectResponse = response.json().await?;
+                let detection = api_response
+                    .data
+                    .detections
+                    .into_iter()
+                    .flatten()
+                    .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+                    .ok_or_else(|| anyhow!("Google returned no language detections"))?;
+
+                Ok((detection.language, detection.confidence))
+            }
+            ApiVersion::V3 => {
+                let response = self
+                    .client
+                    .post(self.v3_url("detectLanguage"))
+                    .query(&[("key", &self.api_key)])
+                    .json(&serde_json::json!({
+                        "content": text,
+                        "mimeType": Self::mime_type(TextFormat::Text),
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let api_response: V3DetectResponse = response.json().await?;
+                let detection = api_response
+                    .languages
+                    .into_iter()
+                    .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+                    .ok_or_else(|| anyhow!("Google returned no language detections"))?;
+
+                Ok((detection.language_code, detection.confidence))
+            }
+        }
+    }
+
+    async fn translate_text(
+        &self,
+        text: &str,
+        format: TextFormat,
+        source: Option<&str>,
+        target: &str,
+    ) -> Result<(String, Option<String>)> {
+        match self.version {
+            ApiVersion::V2 => {
+                let url = format!("{}/language/translate/v2", self.base_url);
+                let mut payload = serde_json::json!({
+                    "q": text,
+                    "target": target,
+                    "format": match format {
+                        TextFormat::Text => "text",
+                        TextFormat::Html => "html",
+                    },
+                });
+                if let Some(source) = source {
+                    payload["source"] = serde_json::Value::String(source.to_string());
+                }
+
+                let response = self
+                    .client
+                    .post(&url)
+                    .query(&[("key", &self.api_key)])
+                    .json(&payload)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let api_response: V2TranslateResponse = response.json().await?;
+                let translation = api_response
+                    .data
+                    .translations
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("Google returned no translations"))?;
+
+                Ok((
+                    translation.translated_text,
+                    translation.detected_source_language,
+                ))
+            }
+            ApiVersion::V3 => {
+                let mut payload = serde_json::json!({
+                    "contents": [text],
+                    "targetLanguageCode": target,
+                    "mimeType": Self::mime_type(format),
+                });
+                if let Some(source) = source {
+                    payload["sourceLanguageCode"] = serde_json::Value::String(source.to_string());
+                }
+
+                let response = self
+                    .client
+                    .post(self.v3_url("translateText"))
+                    .query(&[("key", &self.api_key)])
+                    .json(&payload)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let api_response: V3TranslateResponse = response.json().await?;
+                let translation = api_response
+                    .translations
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("Google returned no translations"))?;
+
+                Ok((
+                    translation.translated_text,
+                    translation.detected_language_code,
+                ))
+            }
+        }
+    }
+}
+
+fn decode_html_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[derive(Deserialize)]
+struct V2TranslateResponse {
+    data: V2TranslateData,
+}
+
+#[derive(Deserialize)]
+struct V2TranslateData {
+    translations: Vec<V2Translation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2Translation {
+    translated_text: String,
+    detected_source_language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct V2DetectResponse {
+    data: V2DetectData,
+}
+
+#[derive(Deserialize)]
+struct V2DetectData {
+    detections: Vec<Vec<V2Detection>>,
+}
+
+#[derive(Deserialize)]
+struct V2Detection {
+    language: String,
+    confidence: f32,
+}
+
+#[derive(Deserialize)]
+struct V3TranslateResponse {
+    translations: Vec<V3Translation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V3Translation {
+    translated_text: String,
+    detected_language_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct V3DetectResponse {
+    languages: Vec<V3Detection>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V3Detection {
+    language_code: String,
+    #[serde(default)]
+    confidence: f32,
+}
+
+#[async_trait]
+impl TranslationProvider for GoogleTranslateProvider {
+    async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResponse> {
+        let target_lang = self
+            .normalize_lang_code(&request.target_lang)
+            .ok_or_else(|| anyhow!("Target language cannot be auto"))?;
+        let (text, format) = self.prepare_text(&request.text);
+
+        // An auto source is detected first so the confidence can be
+        // reported; "und" (undetermined) leaves the guess to the translate
+        // call.
+        let (source_lang, detected_language, confidence) =
+            match self.normalize_lang_code(&request.source_lang) {
+                Some(source) => (Some(source), None, None),
+                None => {
+                    let (language, confidence) = self.detect_language(&request.text).await?;
+                    let source = (language != "und").then(|| language.clone());
+                    (source, Some(language), Some(confidence))
+                }
+            };
+
+        let (translated_text, reported_language) = self
+            .translate_text(&text, format, source_lang.as_deref(), &target_lang)
+            .await?;
+
+        Ok(TranslationResponse {
+            translated_text: self.finish_text(translated_text, format),
+            detected_language: detected_language
+                .filter(|language| language != "und")
+                .or(reported_language),
+            confidence,
+            provider: None,
+        })
+    }
+
+    async fn detect(&self, text: &str) -> Result<Option<Detection>> {
+        let (language, confidence) = self.detect_language(text).await?;
+
+        Ok((language != "und").then_some(Detection {
+            language,
+            confidence: Some(confidence),
+        }))
+    }
+
+    fn name(&self) -> &str {
+        "Google Translate"
+    }
+
+    fn supports_language(&self, _lang: &str) -> bool {
+        true
+    }
+
+    fn calls_per_chunk(&self, request: &TranslationRequest) -> u32 {
+        match self.normalize_lang_code(&request.source_lang) {
+            Some(_) => 1,
+            None => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html_provider() -> GoogleTranslateProvider {
+        let config: ProviderConfig = serde_json::from_value(serde_json::json!({
+            "kind": "google",
+            "api_key": "key",
+            "format": "html",
+        }))
+        .unwrap();
+        GoogleTranslateProvider::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn html_mode_escapes_text_and_protects_placeholders() {
+        let provider = html_provider();
+
+        let (prepared, format) = provider.prepare_text("a < b && [[0]] c> [[x]] [[12]]");
+        assert_eq!(format, TextFormat::Html);
+        assert_eq!(
+            prepared,
+            "a &lt; b &amp;&amp; <span translate=\"no\">[[0]]</span> c&gt; [[x]] <span translate=\"no\">[[12]]</span>"
+        );
+
+        // Google returns entities for anything it escaped itself.
+        let translated = prepared.replace("a &lt; b", "x &lt; y &#39;q&#39;");
+        assert_eq!(
+            provider.finish_text(translated, format),
+            "x < y 'q' && [[0]] c> [[x]] [[12]]"
+        );
+    }
+
+    #[test]
+    fn text_without_placeholders_is_sent_as_plain_text() {
+        let provider = html_provider();
+
+        let (prepared, format) = provider.prepare_text("a < b &amp; c");
+        assert_eq!(format, TextFormat::Text);
+        assert_eq!(prepared, "a < b &amp; c");
+        assert_eq!(provider.finish_text(prepared, format), "a < b &amp; c");
+    }
+}
@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// The provider is skipped until the cooldown window elapses.
+    Open,
+    /// The cooldown elapsed and a single probe request is allowed through.
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half-open",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "🟢",
+            CircuitState::Open => "🔴",
+            CircuitState::HalfOpen => "🟡",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerPolicy {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderHealth {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+    last_error: Option<String>,
+    last_failure_at: Option<DateTime<Utc>>,
+    last_success_at: Option<DateTime<Utc>>,
+    total_successes: u64,
+    total_failures: u64,
+}
+
+impl ProviderHealth {
+    pub fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_in_flight: false,
+            last_error: None,
+            last_failure_at: None,
+            last_success_at: None,
+            total_successes: 0,
+            total_failures: 0,
+        }
+    }
+
+    /// Returns whether a request may be sent to the provider right now,
+    /// moving an open breaker to half-open once its cooldown has elapsed.
+    pub fn try_acquire(&mut self, policy: &BreakerPolicy) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let cooled_down = self
+                    .opened_at
+                    .map(|opened| opened.elapsed() >= policy.cooldown)
+                    .unwrap_or(true);
+
+                if cooled_down {
+                    self.start_probe();
+                    true
+                } else {
+                    false
+                }
+            }
+            CircuitState::HalfOpen => {
+                // A probe that never reported back (e.g. its task was dropped)
+                // must not wedge the breaker, so it expires after one cooldown.
+                let probe_stale = self
+                    .opened_at
+                    .map(|started| started.elapsed() >= policy.cooldown)
+                    .unwrap_or(true);
+
+                if self.probe_in_flight && !probe_stale {
+                    false
+                } else {
+                    self.start_probe();
+                    true
+                }
+            }
+        }
+    }
+
+    fn start_probe(&mut self) {
+        self.state = CircuitState::HalfOpen;
+        self.probe_in_flight = true;
+        self.opened_at = Some(Instant::now());
+    }
+
+    /// Gives back an acquired slot without recording an outcome, e.g. when
+    /// the provider turned out not to support the requested operation.
+    pub fn release(&mut self) {
+        self.probe_in_flight = false;
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.probe_in_flight = false;
+        self.last_success_at = Some(Utc::now());
+        self.total_successes += 1;
+    }
+
+    pub fn record_failure(&mut self, error: &str, policy: &BreakerPolicy) {
+        self.consecutive_failures += 1;
+        self.total_failures += 1;
+        self.probe_in_flight = false;
+        self.last_error = Some(error.to_string());
+        self.last_failure_at = Some(Utc::now());
+
+        let should_open = self.state == CircuitState::HalfOpen
+            || self.consecutive_failures >= policy.failure_threshold;
+
+        if should_open {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(Instant::now());
+        }
+    }
+
+    pub fn snapshot(&self, name: &str, policy: &BreakerPolicy) -> ProviderHealthSnapshot {
+        let retry_in = match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened)) => {
+                Some(policy.cooldown.saturating_sub(opened.elapsed()))
+            }
+            _ => None,
+        };
+
+        ProviderHealthSnapshot {
+            name: name.to_string(),
+            state: self.state,
+            consecutive_failures: self.consecutive_failures,
+            retry_in,
+            last_error: self.last_error.clone(),
+            last_failure_at: self.last_failure_at,
+            last_success_at: self.last_success_at,
+            total_successes: self.total_successes,
+            total_failures: self.total_failures,
+        }
+    }
+}
+
+impl Default for ProviderHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProviderHealthSnapshot {
+    pub name: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub retry_in: Option<Duration>,
+    pub last_error: Option<String>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub total_successes: u64,
+    pub total_failures: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAITING: BreakerPolicy = BreakerPolicy {
+        failure_threshold: 3,
+        cooldown: Duration::from_secs(3600),
+    };
+
+    /// Same threshold, but every cooldown has already elapsed.
+    const COOLED: BreakerPolicy = BreakerPolicy {
+        failure_threshold: 3,
+        cooldown: Duration::ZERO,
+    };
+
+    fn opened() -> ProviderHealth {
+        let mut health = ProviderHealth::new();
+        for _ in 0..WAITING.failure_threshold {
+            assert!(health.try_acquire(&WAITING));
+            health.record_failure("boom", &WAITING);
+        }
+        health
+    }
+
+    #[test]
+    fn opens_after_the_failure_threshold() {
+        let mut health = ProviderHealth::new();
+
+        health.record_failure("boom", &WAITING);
+        health.record_failure("boom", &WAITING);
+        assert_eq!(health.state, CircuitState::Closed);
+        assert!(health.try_acquire(&WAITING));
+
+        // A success in between resets the streak.
+        health.record_success();
+        health.record_failure("boom", &WAITING);
+        health.record_failure("boom", &WAITING);
+        assert_eq!(health.state, CircuitState::Closed);
+
+        health.record_failure("boom", &WAITING);
+        assert_eq!(health.state, CircuitState::Open);
+        assert!(!health.try_acquire(&WAITING));
+
+        let snapshot = health.snapshot("test", &WAITING);
+        assert_eq!(snapshot.consecutive_failures, 3);
+        assert_eq!(snapshot.total_failures, 5);
+        assert_eq!(snapshot.last_error.as_deref(), Some("boom"));
+        assert!(snapshot.retry_in.is_some());
+    }
+
+    #[test]
+    fn cooldown_admits_a_single_probe() {
+        let mut health = opened();
+
+        assert!(health.try_acquire(&COOLED));
+        assert_eq!(health.state, CircuitState::HalfOpen);
+
+        // The probe is still running, so nothing else gets through.
+        assert!(!health.try_acquire(&WAITING));
+        assert!(!health.try_acquire(&WAITING));
+    }
+
+    #[test]
+    fn successful_probe_closes_the_circuit() {
+        let mut health = opened();
+        assert!(health.try_acquire(&COOLED));
+
+        health.record_success();
+
+        assert_eq!(health.state, CircuitState::Closed);
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.try_acquire(&WAITING));
+        assert!(health.try_acquire(&WAITING));
+    }
+
+    #[test]
+    fn failed_probe_reopens_the_circuit() {
+        let mut health = opened();
+        assert!(health.try_acquire(&COOLED));
+
+        health.record_failure("still down", &WAITING);
+
+        assert_eq!(health.state, CircuitState::Open);
+        assert!(!health.try_acquire(&WAITING));
+        assert_eq!(
+            health.snapshot("test", &WAITING).last_error.as_deref(),
+            Some("still down")
+        );
+    }
+
+    #[test]
+    fn stale_probe_is_replaced() {
+        let mut health = opened();
+        assert!(health.try_acquire(&COOLED));
+
+        // The probe never reported back; once a cooldown has passed since it
+        // started, another one is let through.
+        assert!(!health.try_acquire(&WAITING));
+        assert!(health.try_acquire(&COOLED));
+        assert_eq!(health.state, CircuitState::HalfOpen);
+        assert!(!health.try_acquire(&WAITING));
+    }
+
+    #[test]
+    fn release_frees_the_probe_slot() {
+        let mut health = opened();
+        assert!(health.try_acquire(&COOLED));
+        assert!(!health.try_acquire(&WAITING));
+
+        health.release();
+
+        // Still half-open, but the next request may probe straight away.
+        assert_eq!(health.state, CircuitState::HalfOpen);
+        assert!(health.try_acquire(&WAITING));
+        assert!(!health.try_acquire(&WAITING));
+    }
+}
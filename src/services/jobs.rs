@@ -0,0 +1,389 @@
+use crate::db::{DatabasePool, JobRepository};
+use crate::models::TranslationJob;
+use crate::services::filter::SkipReason;
+use crate::services::worker::TranslationWorker;
+use crate::utils::config::JobConfig;
+use anyhow::Result;
+use chrono::Utc;
+use futures::future::join_all;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Mutex, Semaphore};
+use tokio::task::JoinHandle;
+
+/// How long a persisted job may stay claimed by an instance that stopped
+/// without releasing it (a crash) before another start takes it over.
+const CLAIM_LEASE: Duration = Duration::from_secs(60 * 60);
+
+/// A job on its way to the scheduler.
+struct Queued {
+    job: TranslationJob,
+    /// Re-queued by `recover`; such jobs are never dropped for the
+    /// per-guild limit since that would discard persisted work.
+    recovered: bool,
+}
+
+/// Handle for queueing auto-translation jobs. Cloning is cheap.
+#[derive(Clone)]
+pub struct JobQueue {
+    sender: mpsc::Sender<Queued>,
+    /// One permit per job that may wait, covering both the channel and the
+    /// scheduler's queue so `capacity` is the real bound.
+    slots: Arc<Semaphore>,
+    db: DatabasePool,
+    persistent: bool,
+    /// Marks the persisted jobs this process owns.
+    instance: Arc<str>,
+}
+
+/// The consuming side of a `JobQueue`, turned into running workers by
+/// `start`. Jobs queued before that simply wait.
+pub struct JobRunner {
+    receiver: mpsc::Receiver<Queued>,
+    slots: Arc<Semaphore>,
+    workers: usize,
+    capacity: usize,
+    per_guild: usize,
+}
+
+/// The running scheduler and workers.
+pub struct JobWorkers {
+    stop: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl JobQueue {
+    pub fn new(db: DatabasePool, config: &JobConfig) -> (Self, JobRunner) {
+        let capacity = config.capacity.max(1);
+        let (sender, receiver) = mpsc::channel(capacity);
+        let slots = Arc::new(Semaphore::new(capacity));
+
+        let queue = Self {
+            sender,
+            slots: slots.clone(),
+            db,
+            persistent: config.persistent,
+            instance: format!("{}-{}", std::process::id(), Utc::now().timestamp_micros()).into(),
+        };
+        let runner = JobRunner {
+            receiver,
+            slots,
+            workers: config.workers.max(1),
+            capacity,
+            per_guild: config.per_guild.max(1),
+        };
+
+        (queue, runner)
+    }
+
+    /// Queues a job without waiting. Returns `false` when the queue is full
+    /// and the job was dropped; event handling never blocks on translation.
+    pub async fn enqueue(&self, job: TranslationJob) -> Result<bool> {
+        let Ok(slot) = self.slots.try_acquire() else {
+            return Ok(false);
+        };
+
+        let job = if self.persistent {
+            JobRepository::create(self.db.pool(), &job, &self.instance).await?
+        } else {
+            job
+        };
+
+        match self.sender.try_send(Queued {
+            job,
+            recovered: false,
+        }) {
+            Ok(()) => {
+                // Handed back by the scheduler once the job leaves the queue.
+                slot.forget();
+                Ok(true)
+            }
+            Err(e) => {
+                let job = e.into_inner().job;
+                if job.is_persisted() {
+                    JobRepository::delete(self.db.pool(), job.id).await?;
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// Claims and re-queues jobs a previous run left unfinished. Unlike
+    /// `enqueue` this waits for room rather than dropping them.
+    pub async fn recover(&self) -> Result<usize> {
+        if !self.persistent {
+            return Ok(0);
+        }
+
+        let pending =
+            JobRepository::claim_pending(self.db.pool(), &self.instance, CLAIM_LEASE.as_secs_f64())
+                .await?;
+        let count = pending.len();
+
+        for job in pending {
+            let Ok(slot) = self.slots.acquire().await else {
+                break;
+            };
+            let queued = Queued {
+                job,
+                recovered: true,
+            };
+            if self.sender.send(queued).await.is_err() {
+                break;
+            }
+            slot.forget();
+        }
+
+        Ok(count)
+    }
+
+    /// Gives this instance's unfinished persisted jobs back, so the next
+    /// start picks them up right away instead of waiting out the lease.
+    pub async fn release_claims(&self) -> Result<u64> {
+        if !self.persistent {
+            return Ok(0);
+        }
+
+        JobRepository::release_claims(self.db.pool(), &self.instance).await
+    }
+}
+
+impl JobRunner {
+    /// Spawns the scheduler and the worker pool.
+    pub fn start(self, worker: Arc<TranslationWorker>) -> JobWorkers {
+        // Capacity 1 hands each job to whichever worker asks next instead of
+        // letting jobs pile up in front of a busy one.
+        let (ready_sender, ready_receiver) = mpsc::channel(1);
+        let ready_receiver = Arc::new(Mutex::new(ready_receiver));
+
+        let (stop, stopped) = watch::channel(false);
+
+        let mut tasks = vec![tokio::spawn(schedule(
+            self.receiver,
+            ready_sender,
+            stopped,
+            FairQueue::new(self.per_guild),
+            self.slots,
+            worker.clone(),
+        ))];
+
+        for _ in 0..self.workers {
+            tasks.push(tokio::spawn(work(ready_receiver.clone(), worker.clone())));
+        }
+
+        tracing::info!(
+            "Started {} translation workers (queue capacity {}, {} per guild)",
+            self.workers,
+            self.capacity,
+            self.per_guild
+        );
+
+        JobWorkers { stop, tasks }
+    }
+}
+
+impl JobWorkers {
+    /// Stops accepting jobs and waits up to `timeout` for the queued and
+    /// running ones to finish. Returns `false` if the deadline passed first;
+    /// persisted jobs left over are picked up again on the next start.
+    pub async fn shutdown(self, timeout: Duration) -> bool {
+        self.stop.send_replace(true);
+
+        tokio::time::timeout(timeout, join_all(self.tasks))
+            .await
+            .is_ok()
+    }
+}
+
+/// Moves jobs from the queue into per-guild lines and hands them to workers
+/// round-robin, so one busy guild cannot starve the others. New jobs beyond
+/// the per-guild limit are dropped; recovered ones wait in their guild's line
+/// regardless, still bounded by the queue capacity. Once stopped it
+/// refuses new jobs, hands out the ones already queued and exits, which in
+/// turn lets the workers exit.
+async fn schedule(
+    mut incoming: mpsc::Receiver<Queued>,
+    ready: mpsc::Sender<TranslationJob>,
+    mut stopped: watch::Receiver<bool>,
+    mut queue: FairQueue,
+    slots: Arc<Semaphore>,
+    worker: Arc<TranslationWorker>,
+) {
+    let mut stopping = false;
+    let mut closed = false;
+
+    loop {
+        if closed && queue.is_empty() {
+            break;
+        }
+
+        tokio::select! {
+            _ = stopped.changed(), if !stopping => {
+                stopping = true;
+                incoming.close();
+            }
+            job = incoming.recv(), if !closed => match job {
+                Some(Queued { job, recovered }) => {
+                    if recovered || queue.has_room(job.guild_id) {
+                        queue.push(job);
+                    } else {
+                        slots.add_permits(1);
+                        worker.record_skip(&job, SkipReason::QueueFull).await;
+                        worker.finish(&job).await;
+                    }
+                }
+                None => closed = true,
+            },
+            permit = ready.reserve(), if !queue.is_empty() => match permit {
+                Ok(permit) => {
+                    if let Some(job) = queue.pop() {
+                        permit.send(job);
+                        slots.add_permits(1);
+                    }
+                }
+                Err(_) => break,
+            },
+        }
+    }
+}
+
+async fn work(jobs: Arc<Mutex<mpsc::Receiver<TranslationJob>>>, worker: Arc<TranslationWorker>) {
+    loop {
+        let Some(job) = jobs.lock().await.recv().await else {
+            break;
+        };
+
+        worker.process(&job).await;
+        worker.finish(&job).await;
+    }
+}
+
+/// Pending jobs grouped by guild, served one guild at a time in rotation.
+struct FairQueue {
+    pending: HashMap<i64, VecDeque<TranslationJob>>,
+    /// Guilds with pending jobs, in the order they are next served.
+    order: VecDeque<i64>,
+    len: usize,
+    per_guild: usize,
+}
+
+impl FairQueue {
+    fn new(per_guild: usize) -> Self {
+        Self {
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+            len: 0,
+            per_guild,
+        }
+    }
+
+    /// Whether the guild has fewer than `per_guild` jobs waiting.
+    fn has_room(&self, guild_id: i64) -> bool {
+        self.pending
+            .get(&guild_id)
+            .is_none_or(|guild_jobs| guild_jobs.len() < self.per_guild)
+    }
+
+    fn push(&mut self, job: TranslationJob) {
+        let guild_jobs = self.pending.entry(job.guild_id).or_default();
+
+        if guild_jobs.is_empty() {
+            self.order.push_back(job.guild_id);
+        }
+
+        guild_jobs.push_back(job);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<TranslationJob> {
+        let guild_id = self.order.pop_front()?;
+        let guild_jobs = self.pending.get_mut(&guild_id)?;
+        let job = guild_jobs.pop_front()?;
+
+        if guild_jobs.is_empty() {
+            self.pending.remove(&guild_id);
+        } else {
+            self.order.push_back(guild_id);
+        }
+
+        self.len -= 1;
+        Some(job)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn job(guild_id: i64, message_id: i64) -> TranslationJob {
+        TranslationJob {
+            id: 0,
+            guild_id,
+            channel_id: 1,
+            message_id,
+            author_id: 1,
+            author_name: "user".to_string(),
+            display_name: "user".to_string(),
+            avatar_url: None,
+            content: "hello".to_string(),
+            jump_url: String::new(),
+            sent_at: Utc::now(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn drain(queue: &mut FairQueue) -> Vec<(i64, i64)> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|job| (job.guild_id, job.message_id))
+            .collect()
+    }
+
+    #[test]
+    fn alternates_between_guilds() {
+        let mut queue = FairQueue::new(10);
+        for message_id in 1..=3 {
+            queue.push(job(1, message_id));
+        }
+        queue.push(job(2, 10));
+        queue.push(job(2, 11));
+
+        assert_eq!(
+            drain(&mut queue),
+            vec![(1, 1), (2, 10), (1, 2), (2, 11), (1, 3)]
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn new_guild_is_served_before_busy_guild_continues() {
+        let mut queue = FairQueue::new(10);
+        for message_id in 1..=3 {
+            queue.push(job(1, message_id));
+        }
+
+        assert_eq!(queue.pop().map(|job| job.message_id), Some(1));
+        queue.push(job(2, 10));
+
+        assert_eq!(drain(&mut queue), vec![(1, 2), (2, 10), (1, 3)]);
+    }
+
+    #[test]
+    fn limits_jobs_per_guild() {
+        let mut queue = FairQueue::new(2);
+        queue.push(job(1, 1));
+        assert!(queue.has_room(1));
+        queue.push(job(1, 2));
+
+        assert!(!queue.has_room(1));
+        assert!(queue.has_room(2));
+
+        queue.pop();
+        assert!(queue.has_room(1));
+    }
+}
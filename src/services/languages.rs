@@ -0,0 +1,216 @@
+/// A language the bot accepts in commands. `code` is what gets stored and
+/// sent to providers; `aliases` are extra spellings users commonly type.
+#[derive(Debug, Clone, Copy)]
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    aliases: &'static [&'static str],
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "zh",
+        name: "Chinese (Simplified)",
+        aliases: &["chinese", "cn", "zh-cn", "zh-hans", "mandarin"],
+    },
+    Language {
+        code: "zh-TW",
+        name: "Chinese (Traditional)",
+        aliases: &["zh-hant", "zh-hk", "traditional"],
+    },
+    Language {
+        code: "en",
+        name: "English",
+        aliases: &["english", "en-us", "en-gb"],
+    },
+    Language {
+        code: "ja",
+        name: "Japanese",
+        aliases: &["japanese", "jp"],
+    },
+    Language {
+        code: "ko",
+        name: "Korean",
+        aliases: &["korean", "kr"],
+    },
+    Language {
+        code: "de",
+        name: "German",
+        aliases: &["german"],
+    },
+    Language {
+        code: "fr",
+        name: "French",
+        aliases: &["french"],
+    },
+    Language {
+        code: "es",
+        name: "Spanish",
+        aliases: &["spanish"],
+    },
+    Language {
+        code: "it",
+        name: "Italian",
+        aliases: &["italian"],
+    },
+    Language {
+        code: "pt",
+        name: "Portuguese",
+        aliases: &["portuguese", "pt-br", "pt-pt"],
+    },
+    Language {
+        code: "ru",
+        name: "Russian",
+        aliases: &["russian"],
+    },
+    Language {
+        code: "nl",
+        name: "Dutch",
+        aliases: &["dutch"],
+    },
+    Language {
+        code: "pl",
+        name: "Polish",
+        aliases: &["polish"],
+    },
+    Language {
+        code: "uk",
+        name: "Ukrainian",
+        aliases: &["ukrainian"],
+    },
+    Language {
+        code: "tr",
+        name: "Turkish",
+        aliases: &["turkish"],
+    },
+    Language {
+        code: "ar",
+        name: "Arabic",
+        aliases: &["arabic"],
+    },
+    Language {
+        code: "hi",
+        name: "Hindi",
+        aliases: &["hindi"],
+    },
+    Language {
+        code: "th",
+        name: "Thai",
+        aliases: &["thai"],
+    },
+    Language {
+        code: "vi",
+        name: "Vietnamese",
+        aliases: &["vietnamese"],
+    },
+    Language {
+        code: "id",
+        name: "Indonesian",
+        aliases: &["indonesian"],
+    },
+    Language {
+        code: "sv",
+        name: "Swedish",
+        aliases: &["swedish"],
+    },
+    Language {
+        code: "el",
+        name: "Greek",
+        aliases: &["greek"],
+    },
+    Language {
+        code: "he",
+        name: "Hebrew",
+        aliases: &["hebrew", "iw"],
+    },
+];
+
+/// Looks a language up by code, English name or alias, ignoring case.
+pub fn lookup(input: &str) -> Option<&'static Language> {
+    let input = input.trim().to_lowercase();
+
+    LANGUAGES.iter().find(|language| {
+        language.code.eq_ignore_ascii_case(&input)
+            || language.name.eq_ignore_ascii_case(&input)
+            || language.aliases.contains(&input.as_str())
+    })
+}
+
+/// Maps a Discord locale such as `en-US`, `zh-TW` or `es-ES` to a supported
+/// language, trying the full locale, then its base language, then English.
+pub fn from_locale(locale: &str) -> &'static Language {
+    lookup(locale)
+        .or_else(|| locale.split('-').next().and_then(lookup))
+        .or_else(|| lookup("en"))
+        .expect("English is always registered")
+}
+
+/// Display name for a language code, falling back to the code itself.
+pub fn display_name(code: &str) -> String {
+    lookup(code)
+        .map(|language| language.name.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
+/// Country (ISO 3166-1 alpha-2) to language code, for flag-emoji reactions.
+const FLAG_LANGUAGES: &[(&str, &str)] = &[
+    ("CN", "zh"),
+    ("SG", "zh"),
+    ("TW", "zh-TW"),
+    ("HK", "zh-TW"),
+    ("MO", "zh-TW"),
+    ("US", "en"),
+    ("GB", "en"),
+    ("AU", "en"),
+    ("CA", "en"),
+    ("NZ", "en"),
+    ("IE", "en"),
+    ("JP", "ja"),
+    ("KR", "ko"),
+    ("DE", "de"),
+    ("AT", "de"),
+    ("FR", "fr"),
+    ("ES", "es"),
+    ("MX", "es"),
+    ("AR", "es"),
+    ("CO", "es"),
+    ("IT", "it"),
+    ("PT", "pt"),
+    ("BR", "pt"),
+    ("RU", "ru"),
+    ("NL", "nl"),
+    ("PL", "pl"),
+    ("UA", "uk"),
+    ("TR", "tr"),
+    ("SA", "ar"),
+    ("AE", "ar"),
+    ("EG", "ar"),
+    ("IN", "hi"),
+    ("TH", "th"),
+    ("VN", "vi"),
+    ("ID", "id"),
+    ("SE", "sv"),
+    ("GR", "el"),
+    ("IL", "he"),
+];
+
+/// Resolves a flag emoji (a pair of regional indicator symbols) to the
+/// language most commonly spoken in that country.
+pub fn from_flag(emoji: &str) -> Option<&'static Language> {
+    let country = emoji
+        .chars()
+        .map(|c| match c as u32 {
+            0x1F1E6..=0x1F1FF => char::from_u32(c as u32 - 0x1F1E6 + 'A' as u32),
+            _ => None,
+        })
+        .collect::<Option<String>>()?;
+
+    if country.len() != 2 {
+        return None;
+    }
+
+    FLAG_LANGUAGES
+        .iter()
+        .find(|(code, _)| *code == country)
+        .and_then(|(_, language)| lookup(language))
+}
@@ -0,0 +1,453 @@
+//! Masks Discord markup so providers only ever see translatable prose.
+//!
+//! Mentions, custom emoji, timestamps, URLs and code are replaced by numbered
+//! placeholders like `[[0]]` and restored verbatim afterwards. Markdown
+//! markers (`**`, `*`, `__`, `_`, `~~`, `||`) are masked the same way so the
+//! text between them is still translated but the formatting survives.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpanKind {
+    /// Content that must come back byte-for-byte (code, mentions, URLs...).
+    Protected,
+    /// A markdown delimiter wrapping translatable text.
+    Marker,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    text: String,
+    kind: SpanKind,
+    space_before: bool,
+    space_after: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MaskedText {
+    pub text: String,
+    spans: Vec<Span>,
+}
+
+const MARKERS: &[&str] = &["***", "**", "__", "~~", "||", "*", "_"];
+
+impl MaskedText {
+    pub fn mask(input: &str) -> Self {
+        let mut text = String::with_capacity(input.len());
+        let mut spans: Vec<Span> = Vec::new();
+        let mut rest = input;
+
+        while !rest.is_empty() {
+            let prev = text_before(input, rest);
+
+            if let Some((len, kind)) = match_span(rest, prev) {
+                let (span_text, after) = rest.split_at(len);
+                let space_before = prev.map(char::is_whitespace).unwrap_or(true);
+                let space_after = after
+                    .chars()
+                    .next()
+                    .map(char::is_whitespace)
+                    .unwrap_or(true);
+
+                text.push_str(&placeholder(spans.len()));
+                spans.push(Span {
+                    text: span_text.to_string(),
+                    kind,
+                    space_before,
+                    space_after,
+                });
+                rest = after;
+                continue;
+            }
+
+            let mut chars = rest.chars();
+            if let Some(c) = chars.next() {
+                text.push(c);
+            }
+            rest = chars.as_str();
+        }
+
+        Self { text, spans }
+    }
+
+    pub fn has_spans(&self) -> bool {
+        !self.spans.is_empty()
+    }
+
+    /// Puts the masked spans back into `translated`. Providers sometimes pad
+    /// placeholders with spaces or switch to full-width brackets, so both are
+    /// accepted. Protected spans the provider dropped are appended at the end
+    /// rather than lost.
+    pub fn restore(&self, translated: &str) -> String {
+        let mut output = String::with_capacity(translated.len() + self.spans.len() * 8);
+        let mut used = vec![false; self.spans.len()];
+        let mut rest = translated;
+        let mut trim_leading = false;
+
+        while !rest.is_empty() {
+            if let Some((index, len)) = parse_placeholder(rest) {
+                if let Some(span) = self.spans.get(index) {
+                    if span.kind == SpanKind::Marker && !span.space_before {
+                        let trimmed = output.trim_end_matches([' ', '\t']).len();
+                        output.truncate(trimmed);
+                    }
+                    output.push_str(&span.text);
+                    used[index] = true;
+                    trim_leading = span.kind == SpanKind::Marker && !span.space_after;
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+
+            let mut chars = rest.chars();
+            let c = chars.next().unwrap_or_default();
+            rest = chars.as_str();
+
+            if trim_leading && (c == ' ' || c == '\t') {
+                continue;
+            }
+            trim_leading = false;
+            output.push(c);
+        }
+
+        for (span, _) in self
+            .spans
+            .iter()
+            .zip(used)
+            .filter(|(span, used)| !used && span.kind == SpanKind::Protected)
+        {
+            if !output.is_empty() && !output.ends_with(char::is_whitespace) {
+                output.push(' ');
+            }
+            output.push_str(&span.text);
+        }
+
+        output
+    }
+}
+
+fn placeholder(index: usize) -> String {
+    format!("[[{}]]", index)
+}
+
+fn text_before<'a>(input: &'a str, rest: &'a str) -> Option<char> {
+    input[..input.len() - rest.len()].chars().next_back()
+}
+
+/// Returns the byte length and kind of a maskable span starting at `rest`.
+fn match_span(rest: &str, prev: Option<char>) -> Option<(usize, SpanKind)> {
+    if let Some(after) = rest.strip_prefix("```") {
+        let len = after.find("```").map(|end| end + 6).unwrap_or(rest.len());
+        return Some((len, SpanKind::Protected));
+    }
+
+    if let Some(after) = rest.strip_prefix('`') {
+        if let Some(end) = after.find('`') {
+            return Some((end + 2, SpanKind::Protected));
+        }
+    }
+
+    // Literal placeholder-looking text is masked too so restore never
+    // confuses user content with our own placeholders.
+    if let Some(after) = rest.strip_prefix("[[") {
+        if let Some(end) = after.find("]]") {
+            return Some((end + 4, SpanKind::Protected));
+        }
+    }
+
+    if rest.starts_with('<') {
+        if let Some(end) = rest.find('>') {
+            if is_discord_tag(&rest[1..end]) {
+                return Some((end + 1, SpanKind::Protected));
+            }
+        }
+    }
+
+    if rest.starts_with("http://") || rest.starts_with("https://") {
+        return Some((url_len(rest), SpanKind::Protected));
+    }
+
+    if let Some(after) = rest.strip_prefix('\\') {
+        let escaped = after.chars().next().filter(|c| c.is_ascii_punctuation())?;
+        return Some((1 + escaped.len_utf8(), SpanKind::Protected));
+    }
+
+    for marker in MARKERS {
+        if !rest.starts_with(marker) {
+            continue;
+        }
+        if *marker == "_" {
+            // Discord ignores underscores inside words (snake_case).
+            let next = rest[1..].chars().next();
+            let inside_word = prev.map(char::is_alphanumeric).unwrap_or(false)
+                && next.map(char::is_alphanumeric).unwrap_or(false);
+            if inside_word {
+                return None;
+            }
+        }
+        return Some((marker.len(), SpanKind::Marker));
+    }
+
+    None
+}
+
+fn is_discord_tag(inner: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    if let Some(id) = inner
+        .strip_prefix("@&")
+        .or_else(|| inner.strip_prefix("@!"))
+        .or_else(|| inner.strip_prefix('@'))
+        .or_else(|| inner.strip_prefix('#'))
+    {
+        return digits(id);
+    }
+
+    if let Some(emoji) = inner.strip_prefix("a:").or_else(|| inner.strip_prefix(':')) {
+        return emoji
+            .rsplit_once(':')
+            .map(|(name, id)| !name.is_empty() && digits(id))
+            .unwrap_or(false);
+    }
+
+    if let Some(timestamp) = inner.strip_prefix("t:") {
+        let seconds = timestamp.split(':').next().unwrap_or_default();
+        return digits(seconds.trim_start_matches('-'));
+    }
+
+    if let Some(command) = inner.strip_prefix('/') {
+        return command
+            .rsplit_once(':')
+            .map(|(name, id)| !name.is_empty() && digits(id))
+            .unwrap_or(false);
+    }
+
+    inner.starts_with("http://") || inner.starts_with("https://")
+}
+
+/// URL length up to the next whitespace, minus trailing sentence punctuation
+/// and an unbalanced closing parenthesis.
+fn url_len(rest: &str) -> usize {
+    let mut url = &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())];
+
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', '!', '?', ';', ':', '\'', '"']);
+        let trimmed = if trimmed.ends_with(')')
+            && trimmed.matches(')').count() > trimmed.matches('(').count()
+        {
+            &trimmed[..trimmed.len() - 1]
+        } else {
+            trimmed
+        };
+
+        if trimmed.len() == url.len() {
+            return url.len();
+        }
+        url = trimmed;
+    }
+}
+
+/// Parses `[[N]]` with optional inner spaces and full-width brackets,
+/// returning the index and the byte length consumed.
+fn parse_placeholder(rest: &str) -> Option<(usize, usize)> {
+    let mut chars = rest.char_indices().peekable();
+
+    for _ in 0..2 {
+        let (_, c) = chars.next()?;
+        if c != '[' && c != '［' && c != '【' {
+            return None;
+        }
+    }
+
+    while chars.peek().map(|(_, c)| *c == ' ').unwrap_or(false) {
+        chars.next();
+    }
+
+    let mut index = String::new();
+    while let Some((_, c)) = chars.peek().copied() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        index.push(c);
+        chars.next();
+    }
+    if index.is_empty() {
+        return None;
+    }
+
+    while chars.peek().map(|(_, c)| *c == ' ').unwrap_or(false) {
+        chars.next();
+    }
+
+    let mut end = 0;
+    for _ in 0..2 {
+        let (i, c) = chars.next()?;
+        if c != ']' && c != '］' && c != '】' {
+            return None;
+        }
+        end = i + c.len_utf8();
+    }
+
+    Some((index.parse().ok()?, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(input: &str) {
+        let masked = MaskedText::mask(input);
+        assert_eq!(
+            masked.restore(&masked.text),
+            input,
+            "masked as {:?}",
+            masked.text
+        );
+    }
+
+    fn assert_protected(input: &str, span: &str) {
+        let masked = MaskedText::mask(input);
+        assert!(
+            !masked.text.contains(span),
+            "{:?} leaked into {:?}",
+            span,
+            masked.text
+        );
+        assert_round_trip(input);
+    }
+
+    #[test]
+    fn masks_user_mentions() {
+        assert_protected(
+            "hello <@123456789012345678> how are you",
+            "<@123456789012345678>",
+        );
+        assert_protected("hello <@!123456789012345678>", "<@!123456789012345678>");
+    }
+
+    #[test]
+    fn masks_channel_and_role_mentions() {
+        assert_protected("see <#987654321098765432> please", "<#987654321098765432>");
+        assert_protected("ping <@&111222333444555666> now", "<@&111222333444555666>");
+    }
+
+    #[test]
+    fn masks_custom_emoji() {
+        assert_protected("nice <:pepe:123456789> work", "<:pepe:123456789>");
+        assert_protected(
+            "nice <a:party_blob:123456789> work",
+            "<a:party_blob:123456789>",
+        );
+    }
+
+    #[test]
+    fn masks_timestamps() {
+        assert_protected("meet at <t:1700000000:F> ok", "<t:1700000000:F>");
+        assert_protected("meet <t:1700000000>", "<t:1700000000>");
+    }
+
+    #[test]
+    fn masks_slash_command_mentions() {
+        assert_protected("run </translate:123456789> now", "</translate:123456789>");
+    }
+
+    #[test]
+    fn masks_urls() {
+        assert_protected(
+            "look https://example.com/a?b=c#d here",
+            "https://example.com/a?b=c#d",
+        );
+        assert_protected(
+            "no embed <https://example.com> here",
+            "<https://example.com>",
+        );
+    }
+
+    #[test]
+    fn url_excludes_trailing_punctuation() {
+        let masked = MaskedText::mask("go to https://example.com.");
+        assert!(masked.text.ends_with("]]."));
+        assert_round_trip("go to https://example.com.");
+        assert_round_trip("(see https://en.wikipedia.org/wiki/Rust_(language))");
+    }
+
+    #[test]
+    fn masks_inline_code() {
+        assert_protected("run `cargo build` first", "cargo build");
+    }
+
+    #[test]
+    fn masks_fenced_code_blocks() {
+        let input = "before\n```rust\nfn main() {}\n```\nafter";
+        assert_protected(input, "fn main()");
+        assert_protected("unterminated ```code here", "code here");
+    }
+
+    #[test]
+    fn keeps_markdown_inner_text_translatable() {
+        for input in [
+            "this is **bold** text",
+            "this is *italic* text",
+            "this is ***both*** text",
+            "this is __underlined__ text",
+            "this is _italic_ text",
+            "this is ~~struck~~ text",
+            "this is ||a spoiler|| text",
+        ] {
+            let masked = MaskedText::mask(input);
+            assert!(
+                !masked.text.contains(['*', '_', '~', '|']),
+                "{:?}",
+                masked.text
+            );
+            assert_round_trip(input);
+        }
+    }
+
+    #[test]
+    fn leaves_intraword_underscores_alone() {
+        let masked = MaskedText::mask("use snake_case names");
+        assert_eq!(masked.text, "use snake_case names");
+        assert!(!masked.has_spans());
+    }
+
+    #[test]
+    fn masks_escaped_characters() {
+        assert_protected("not \\*italic\\* here", "\\*");
+    }
+
+    #[test]
+    fn masks_literal_placeholders() {
+        assert_round_trip("literal [[0]] text with **bold**");
+    }
+
+    #[test]
+    fn round_trips_mixed_message() {
+        assert_round_trip(
+            "<@1> **看** ||剧透|| <:e:2> `x` https://a.b/c <t:3:R> <#4> <@&5> ~~no~~ __u__\n```\ncode\n```",
+        );
+        assert_round_trip("");
+        assert_round_trip("plain text only");
+    }
+
+    #[test]
+    fn restore_tolerates_provider_spacing() {
+        let masked = MaskedText::mask("hi <@1> **there**");
+        assert_eq!(masked.text, "hi [[0]] [[1]]there[[2]]");
+        assert_eq!(
+            masked.restore("hallo [[ 0 ]] [[1]] dort [[2]]"),
+            "hallo <@1> **dort**"
+        );
+        assert_eq!(
+            masked.restore("你好［［0］］ 【【1】】那里【【2】】"),
+            "你好<@1> **那里**"
+        );
+    }
+
+    #[test]
+    fn restore_appends_dropped_protected_spans() {
+        let masked = MaskedText::mask("see https://example.com now");
+        assert_eq!(
+            masked.restore("siehe jetzt"),
+            "siehe jetzt https://example.com"
+        );
+    }
+}
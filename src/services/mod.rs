@@ -1,3 +1,26 @@
+pub mod cache;
+pub mod channels;
+pub mod chunker;
+pub mod deepl;
+pub mod detect;
+pub mod filter;
+pub mod google;
+pub mod health;
+pub mod jobs;
+pub mod languages;
+pub mod llm;
+pub mod markup;
+pub mod quota;
+pub mod settings;
 pub mod translator;
+pub mod webhook;
+pub mod worker;
 
-pub use translator::{LibreTranslateProvider, LingvaProvider, MyMemoryProvider, TranslationService};
+pub use cache::TranslationCache;
+pub use channels::ChannelConfigCache;
+pub use health::BreakerPolicy;
+pub use jobs::JobQueue;
+pub use quota::ProviderLimits;
+pub use settings::GuildSettingsCache;
+pub use translator::{build_provider, TranslationService};
+pub use worker::TranslationWorker;
@@ -0,0 +1,303 @@
+use crate::utils::config::{ProviderConfig, ProviderKind};
+use chrono::{NaiveDate, Utc};
+use std::time::Instant;
+
+/// Requests per minute the public libretranslate.com instance tolerates
+/// before it starts rejecting calls.
+const LIBRETRANSLATE_PUBLIC_RATE: u32 = 20;
+/// MyMemory's free daily allowance, raised when an email is supplied.
+const MYMEMORY_ANONYMOUS_QUOTA: u64 = 5_000;
+const MYMEMORY_EMAIL_QUOTA: u64 = 50_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProviderLimits {
+    pub requests_per_minute: Option<u32>,
+    pub daily_char_quota: Option<u64>,
+}
+
+impl ProviderLimits {
+    /// Configured limits, falling back to the known limits of the public
+    /// free tiers. An explicit `0` turns a limit off.
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        let public_libretranslate = config.kind == ProviderKind::LibreTranslate
+            && config
+                .base_url
+                .as_deref()
+                .is_none_or(|url| url.contains("libretranslate.com"));
+
+        let default_rate = public_libretranslate.then_some(LIBRETRANSLATE_PUBLIC_RATE);
+        let default_quota = match config.kind {
+            ProviderKind::MyMemory if config.email.is_some() => Some(MYMEMORY_EMAIL_QUOTA),
+            ProviderKind::MyMemory => Some(MYMEMORY_ANONYMOUS_QUOTA),
+            _ => None,
+        };
+
+        Self {
+            requests_per_minute: config
+                .rate_limit_per_min
+                .or(default_rate)
+                .filter(|&rate| rate > 0),
+            daily_char_quota: config
+                .daily_char_quota
+                .or(default_quota)
+                .filter(|&quota| quota > 0),
+        }
+    }
+}
+
+/// Holds up to a minute's worth of requests and refills continuously, so
+/// short bursts pass while the average stays under the limit.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = f64::from(per_minute);
+
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            refilled_at: now,
+        }
+    }
+
+    /// Takes one token per request. A batch larger than the bucket waits
+    /// for a full bucket and leaves it in debt, so the average rate holds.
+    fn try_take(&mut self, count: u32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        self.refilled_at = now;
+
+        let count = f64::from(count);
+        if self.tokens >= count.min(self.capacity) {
+            self.tokens -= count;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn give_back(&mut self, count: u32) {
+        self.tokens = (self.tokens + f64::from(count)).min(self.capacity);
+    }
+}
+
+/// Request rate and daily character usage of one provider. Days follow UTC,
+/// which is when the free tiers reset.
+#[derive(Debug)]
+pub struct ProviderQuota {
+    limits: ProviderLimits,
+    bucket: Option<TokenBucket>,
+    day: NaiveDate,
+    used_today: u64,
+}
+
+impl ProviderQuota {
+    pub fn new(limits: ProviderLimits) -> Self {
+        Self {
+            limits,
+            bucket: limits
+                .requests_per_minute
+                .map(|rate| TokenBucket::new(rate, Instant::now())),
+            day: Utc::now().date_naive(),
+            used_today: 0,
+        }
+    }
+
+    /// Returns whether `requests` calls carrying `chars` characters may be
+    /// sent now. When they may, the characters are reserved against today's
+    /// quota and the rate-limit tokens taken, so concurrent callers cannot
+    /// overshoot either limit.
+    pub fn try_acquire(&mut self, chars: u64, requests: u32) -> bool {
+        self.try_acquire_at(chars, requests, Instant::now(), Utc::now().date_naive())
+    }
+
+    fn try_acquire_at(&mut self, chars: u64, requests: u32, now: Instant, today: NaiveDate) -> bool {
+        self.roll_over(today);
+
+        if self
+            .limits
+            .daily_char_quota
+            .is_some_and(|quota| self.used_today + chars > quota)
+        {
+            return false;
+        }
+
+        if !self
+            .bucket
+            .as_mut()
+            .is_none_or(|bucket| bucket.try_take(requests, now))
+        {
+            return false;
+        }
+
+        self.used_today += chars;
+        true
+    }
+
+    /// Returns everything `try_acquire` took for calls that were never sent
+    /// to the provider, e.g. a detection call it has no endpoint for.
+    pub fn release(&mut self, chars: u64, requests: u32) {
+        if let Some(bucket) = &mut self.bucket {
+            bucket.give_back(requests);
+        }
+        self.refund(chars);
+    }
+
+    /// Returns the reserved characters of a failed request. Its tokens stay
+    /// spent, since the provider did see the calls.
+    pub fn refund(&mut self, chars: u64) {
+        self.used_today = self.used_today.saturating_sub(chars);
+    }
+
+    /// Restores usage persisted by an earlier run.
+    pub fn restore(&mut self, day: NaiveDate, used: u64) {
+        if day == self.day {
+            self.used_today = self.used_today.max(used);
+        }
+    }
+
+    pub fn snapshot(&self, name: &str) -> QuotaSnapshot {
+        let today = Utc::now().date_naive();
+        let used_today = if today == self.day {
+            self.used_today
+        } else {
+            0
+        };
+
+        QuotaSnapshot {
+            name: name.to_string(),
+            requests_per_minute: self.limits.requests_per_minute,
+            daily_char_quota: self.limits.daily_char_quota,
+            used_today,
+        }
+    }
+
+    fn roll_over(&mut self, today: NaiveDate) {
+        if today != self.day {
+            self.day = today;
+            self.used_today = 0;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuotaSnapshot {
+    pub name: String,
+    pub requests_per_minute: Option<u32>,
+    pub daily_char_quota: Option<u64>,
+    pub used_today: u64,
+}
+
+impl QuotaSnapshot {
+    pub fn remaining(&self) -> Option<u64> {
+        self.daily_char_quota
+            .map(|quota| quota.saturating_sub(self.used_today))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limits(rate: Option<u32>, quota: Option<u64>) -> ProviderLimits {
+        ProviderLimits {
+            requests_per_minute: rate,
+            daily_char_quota: quota,
+        }
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, start);
+
+        assert!(bucket.try_take(1, start));
+        assert!(bucket.try_take(1, start));
+        assert!(!bucket.try_take(1, start));
+
+        // Two per minute means one token every 30 seconds.
+        assert!(!bucket.try_take(1, start + Duration::from_secs(20)));
+        assert!(bucket.try_take(1, start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn bucket_takes_a_token_per_call() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(4, start);
+
+        assert!(bucket.try_take(3, start));
+        assert!(!bucket.try_take(2, start));
+        assert!(bucket.try_take(1, start));
+    }
+
+    #[test]
+    fn oversized_batch_waits_for_a_full_bucket_and_leaves_debt() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, start);
+
+        assert!(bucket.try_take(4, start));
+        // Two tokens of debt take a minute to repay before the next call.
+        assert!(!bucket.try_take(1, start + Duration::from_secs(59)));
+        assert!(bucket.try_take(1, start + Duration::from_secs(91)));
+    }
+
+    #[test]
+    fn quota_reserves_characters_until_refunded() {
+        let today = Utc::now().date_naive();
+        let mut quota = ProviderQuota::new(limits(None, Some(100)));
+
+        assert!(quota.try_acquire_at(80, 1, Instant::now(), today));
+        assert!(quota.try_acquire_at(20, 1, Instant::now(), today));
+        assert!(!quota.try_acquire_at(1, 1, Instant::now(), today));
+        assert_eq!(quota.snapshot("test").remaining(), Some(0));
+
+        quota.refund(20);
+        assert_eq!(quota.snapshot("test").remaining(), Some(20));
+        assert!(!quota.try_acquire_at(21, 1, Instant::now(), today));
+    }
+
+    #[test]
+    fn rate_limited_request_reserves_nothing() {
+        let today = Utc::now().date_naive();
+        let mut quota = ProviderQuota::new(limits(Some(1), Some(100)));
+
+        assert!(quota.try_acquire_at(10, 1, Instant::now(), today));
+        assert!(!quota.try_acquire_at(10, 1, Instant::now(), today));
+        assert_eq!(quota.snapshot("test").used_today, 10);
+
+        quota.release(10, 1);
+        assert_eq!(quota.snapshot("test").used_today, 0);
+        assert!(quota.try_acquire_at(10, 1, Instant::now(), today));
+    }
+
+    #[test]
+    fn quota_resets_on_a_new_day() {
+        let today = Utc::now().date_naive();
+        let mut quota = ProviderQuota::new(limits(None, Some(100)));
+        assert!(quota.try_acquire_at(100, 1, Instant::now(), today));
+
+        assert!(!quota.try_acquire_at(1, 1, Instant::now(), today));
+        assert!(quota.try_acquire_at(1, 1, Instant::now(), today.succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn restore_ignores_other_days() {
+        let today = Utc::now().date_naive();
+        let mut quota = ProviderQuota::new(limits(None, Some(100)));
+
+        quota.restore(today.pred_opt().unwrap(), 90);
+        assert_eq!(quota.snapshot("test").used_today, 0);
+
+        quota.restore(today, 90);
+        assert_eq!(quota.snapshot("test").used_today, 90);
+    }
+}
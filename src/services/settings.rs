@@ -0,0 +1,52 @@
+use crate::db::{DatabasePool, GuildRepository};
+use crate::models::GuildSettings;
+use dashmap::DashMap;
+use serenity::all::GuildId;
+
+/// In-memory view of `guild_settings`, read on every message. Commands that
+/// change a guild's settings must call `invalidate` afterwards.
+pub struct GuildSettingsCache {
+    db: DatabasePool,
+    entries: DashMap<i64, GuildSettings>,
+}
+
+impl GuildSettingsCache {
+    pub fn new(db: DatabasePool) -> Self {
+        Self {
+            db,
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Settings for a guild, or the defaults for guilds that never changed
+    /// anything. Lookup failures fall back to the defaults without caching.
+    pub async fn get(&self, guild_id: GuildId) -> GuildSettings {
+        let key = guild_id.get() as i64;
+
+        if let Some(settings) = self.entries.get(&key) {
+            return settings.clone();
+        }
+
+        match GuildRepository::get(self.db.pool(), key).await {
+            Ok(stored) => {
+                let settings = stored.unwrap_or_else(|| GuildSettings {
+                    guild_id: key,
+                    ..GuildSettings::default()
+                });
+                self.entries.insert(key, settings.clone());
+                settings
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load guild settings for {}: {}", guild_id, e);
+                GuildSettings {
+                    guild_id: key,
+                    ..GuildSettings::default()
+                }
+            }
+        }
+    }
+
+    pub fn invalidate(&self, guild_id: GuildId) {
+        self.entries.remove(&(guild_id.get() as i64));
+    }
+}
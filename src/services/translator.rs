@@ -1,13 +1,53 @@
+use crate::db::{DatabasePool, UsageRepository};
+use crate::services::cache::{CacheKey, CacheStats, TranslationCache};
+use crate::services::chunker::{split_text, Unit};
+use crate::services::deepl::DeepLProvider;
+use crate::services::detect::{guess_script, Detection};
+use crate::services::google::GoogleTranslateProvider;
+use crate::services::health::{BreakerPolicy, ProviderHealth, ProviderHealthSnapshot};
+use crate::services::llm::LlmProvider;
+use crate::services::markup::MaskedText;
+use crate::services::quota::{ProviderLimits, ProviderQuota, QuotaSnapshot};
+use crate::utils::config::{ProviderConfig, ProviderKind};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent("MegaChinese-Bot/1.0")
+        .build()
+        .unwrap()
+}
+
+/// Builds the provider described by one entry of the configured chain.
+pub fn build_provider(config: &ProviderConfig) -> Result<Arc<dyn TranslationProvider>> {
+    let provider: Arc<dyn TranslationProvider> = match config.kind {
+        ProviderKind::LibreTranslate => Arc::new(LibreTranslateProvider::from_config(config)),
+        ProviderKind::MyMemory => Arc::new(MyMemoryProvider::from_config(config)),
+        ProviderKind::Lingva => Arc::new(LingvaProvider::from_config(config)),
+        ProviderKind::DeepL => Arc::new(DeepLProvider::from_config(config)?),
+        ProviderKind::Google => Arc::new(GoogleTranslateProvider::from_config(config)?),
+        ProviderKind::Llm => Arc::new(LlmProvider::from_config(config)?),
+    };
+
+    Ok(provider)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationRequest {
     pub text: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// Whether LLM-backed providers may be used; guilds opt in explicitly.
+    #[serde(default)]
+    pub allow_llm: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +55,10 @@ pub struct TranslationResponse {
     pub translated_text: String,
     pub detected_language: Option<String>,
     pub confidence: Option<f32>,
+    /// Name of the provider that produced the translation, filled in by
+    /// `TranslationService`.
+    #[serde(default)]
+    pub provider: Option<String>,
 }
 
 #[async_trait]
@@ -22,34 +66,61 @@ pub trait TranslationProvider: Send + Sync {
     async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResponse>;
     fn name(&self) -> &str;
     fn supports_language(&self, lang: &str) -> bool;
+
+    /// Detects the language of `text`. Providers without a detection
+    /// endpoint return `Ok(None)`.
+    async fn detect(&self, _text: &str) -> Result<Option<Detection>> {
+        Ok(None)
+    }
+
+    /// Largest request text the provider accepts in one call, and the unit
+    /// it is measured in. Longer texts are split at sentence boundaries by
+    /// the service.
+    fn max_text_len(&self) -> Option<(usize, Unit)> {
+        None
+    }
+
+    /// HTTP requests one chunk costs, each taking a rate-limit token.
+    fn calls_per_chunk(&self, _request: &TranslationRequest) -> u32 {
+        1
+    }
+
+    fn is_llm(&self) -> bool {
+        false
+    }
 }
 
 pub struct LibreTranslateProvider {
     base_url: String,
+    api_key: Option<String>,
     client: reqwest::Client,
 }
 
 impl LibreTranslateProvider {
+    pub const DEFAULT_URL: &'static str = "https://libretranslate.com";
+
     pub fn new() -> Self {
+        Self::with_custom_instance(Self::DEFAULT_URL.to_string())
+    }
+
+    pub fn with_custom_instance(base_url: String) -> Self {
         Self {
-            base_url: "https://libretranslate.com".to_string(),
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .user_agent("MegaChinese-Bot/1.0")
-                .build()
-                .unwrap(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+            client: http_client(DEFAULT_TIMEOUT),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn with_custom_instance(base_url: String) -> Self {
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| Self::DEFAULT_URL.to_string());
+
         Self {
-            base_url,
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .user_agent("MegaChinese-Bot/1.0")
-                .build()
-                .unwrap(),
+            api_key: config.api_key.clone(),
+            client: http_client(config.timeout()),
+            ..Self::with_custom_instance(base_url)
         }
     }
 
@@ -95,13 +166,17 @@ impl TranslationProvider for LibreTranslateProvider {
         let source_lang = self.normalize_lang_code(&request.source_lang);
         let target_lang = self.normalize_lang_code(&request.target_lang);
 
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "q": request.text,
             "source": source_lang,
             "target": target_lang,
             "format": "text"
         });
 
+        if let Some(api_key) = &self.api_key {
+            payload["api_key"] = serde_json::Value::String(api_key.clone());
+        }
+
         let response = self
             .client
             .post(&url)
@@ -119,9 +194,37 @@ impl TranslationProvider for LibreTranslateProvider {
             translated_text: api_response.translated_text,
             detected_language: detected_lang,
             confidence,
+            provider: None,
         })
     }
 
+    async fn detect(&self, text: &str) -> Result<Option<Detection>> {
+        let url = format!("{}/detect", self.base_url);
+
+        let mut payload = serde_json::json!({ "q": text });
+        if let Some(api_key) = &self.api_key {
+            payload["api_key"] = serde_json::Value::String(api_key.clone());
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let detections: Vec<DetectedLanguage> = response.json().await?;
+
+        Ok(detections
+            .into_iter()
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+            .map(|d| Detection {
+                language: d.language,
+                confidence: Some(d.confidence / 100.0),
+            }))
+    }
+
     fn name(&self) -> &str {
         "LibreTranslate"
     }
@@ -138,22 +241,38 @@ impl Default for LibreTranslateProvider {
 }
 
 pub struct MyMemoryProvider {
+    base_url: String,
+    email: Option<String>,
     client: reqwest::Client,
 }
 
 impl MyMemoryProvider {
+    pub const DEFAULT_URL: &'static str = "https://api.mymemory.translated.net";
+
     pub fn new() -> Self {
         Self {
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .user_agent("MegaChinese-Bot/1.0")
-                .build()
-                .unwrap(),
+            base_url: Self::DEFAULT_URL.to_string(),
+            email: None,
+            client: http_client(DEFAULT_TIMEOUT),
+        }
+    }
+
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            email: config.email.clone(),
+            client: http_client(config.timeout()),
         }
     }
 
     fn normalize_lang_code(&self, lang: &str) -> String {
         match lang.to_lowercase().as_str() {
+            "auto" => "Autodetect".to_string(),
             "zh" | "zh-cn" | "zh-hans" | "chinese" => "zh-CN".to_string(),
             "zh-tw" | "zh-hant" => "zh-TW".to_string(),
             "en" | "english" => "en-US".to_string(),
@@ -188,13 +307,18 @@ impl TranslationProvider for MyMemoryProvider {
         let source_lang = self.normalize_lang_code(&request.source_lang);
         let target_lang = self.normalize_lang_code(&request.target_lang);
 
-        let url = format!(
-            "https://api.mymemory.translated.net/get?q={}&langpair={}|{}",
+        let mut url = format!(
+            "{}/get?q={}&langpair={}|{}",
+            self.base_url,
             urlencoding::encode(&request.text),
             source_lang,
             target_lang
         );
 
+        if let Some(email) = &self.email {
+            url.push_str(&format!("&de={}", urlencoding::encode(email)));
+        }
+
         let response = self
             .client
             .get(&url)
@@ -208,6 +332,7 @@ impl TranslationProvider for MyMemoryProvider {
             translated_text: api_response.response_data.translated_text,
             detected_language: None,
             confidence: None,
+            provider: None,
         })
     }
 
@@ -215,6 +340,10 @@ impl TranslationProvider for MyMemoryProvider {
         "MyMemory"
     }
 
+    fn max_text_len(&self) -> Option<(usize, Unit)> {
+        Some((500, Unit::Bytes))
+    }
+
     fn supports_language(&self, _lang: &str) -> bool {
         true
     }
@@ -232,14 +361,24 @@ pub struct LingvaProvider {
 }
 
 impl LingvaProvider {
+    pub const DEFAULT_URL: &'static str = "https://lingva.ml";
+
     pub fn new() -> Self {
         Self {
-            base_url: "https://lingva.ml".to_string(),
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .user_agent("MegaChinese-Bot/1.0")
-                .build()
-                .unwrap(),
+            base_url: Self::DEFAULT_URL.to_string(),
+            client: http_client(DEFAULT_TIMEOUT),
+        }
+    }
+
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            client: http_client(config.timeout()),
         }
     }
 
@@ -284,6 +423,7 @@ impl TranslationProvider for LingvaProvider {
             translated_text: api_response.translation,
             detected_language: None,
             confidence: None,
+            provider: None,
         })
     }
 
@@ -291,6 +431,13 @@ impl TranslationProvider for LingvaProvider {
         "Lingva"
     }
 
+    fn max_text_len(&self) -> Option<(usize, Unit)> {
+        // The text travels percent-encoded in the URL path, where CJK text
+        // takes three times its UTF-8 size; stay well under the ~2000
+        // character URLs servers and proxies reliably accept.
+        Some((1500, Unit::UrlEncoded))
+    }
+
     fn supports_language(&self, _lang: &str) -> bool {
         true
     }
@@ -302,69 +449,383 @@ impl Default for LingvaProvider {
     }
 }
 
+struct ProviderSlot {
+    /// Distinguishes several configured instances of the same provider in
+    /// usage records and stats.
+    id: String,
+    provider: Arc<dyn TranslationProvider>,
+    health: Mutex<ProviderHealth>,
+    quota: Mutex<ProviderQuota>,
+}
+
 pub struct TranslationService {
-    providers: Vec<Arc<dyn TranslationProvider>>,
+    providers: Vec<ProviderSlot>,
+    cache: Option<TranslationCache>,
+    breaker: BreakerPolicy,
+    usage_store: Option<DatabasePool>,
 }
 
 impl TranslationService {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            cache: None,
+            breaker: BreakerPolicy::default(),
+            usage_store: None,
+        }
+    }
+
+    pub fn add_provider(&mut self, provider: Arc<dyn TranslationProvider>, limits: ProviderLimits) {
+        let instances = self
+            .providers
+            .iter()
+            .filter(|slot| slot.provider.name() == provider.name())
+            .count();
+        let id = match instances {
+            0 => provider.name().to_string(),
+            n => format!("{} #{}", provider.name(), n + 1),
+        };
+
+        self.providers.push(ProviderSlot {
+            id,
+            provider,
+            health: Mutex::new(ProviderHealth::new()),
+            quota: Mutex::new(ProviderQuota::new(limits)),
+        });
+    }
+
+    /// Persists daily character usage so quotas survive restarts.
+    pub fn set_usage_store(&mut self, db: DatabasePool) {
+        self.usage_store = Some(db);
+    }
+
+    /// Picks up today's usage recorded by an earlier run.
+    pub async fn load_usage(&self) -> Result<()> {
+        let Some(db) = &self.usage_store else {
+            return Ok(());
+        };
+
+        let today = Utc::now().date_naive();
+
+        for slot in &self.providers {
+            let used = UsageRepository::get(db.pool(), &slot.id, today).await?;
+
+            if let Some(used) = used {
+                slot.quota
+                    .lock()
+                    .unwrap()
+                    .restore(today, used.max(0) as u64);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_cache(&mut self, cache: TranslationCache) {
+        self.cache = Some(cache);
+    }
+
+    pub fn set_breaker_policy(&mut self, policy: BreakerPolicy) {
+        self.breaker = policy;
+    }
+
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|slot| slot.provider.name().to_string())
+            .collect()
+    }
+
+    /// Drops expired entries from the persistent cache, if there is one.
+    pub async fn purge_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.purge_expired().await;
         }
     }
 
-    pub fn add_provider(&mut self, provider: Arc<dyn TranslationProvider>) {
-        self.providers.push(provider);
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    pub fn provider_quotas(&self) -> Vec<QuotaSnapshot> {
+        self.providers
+            .iter()
+            .map(|slot| slot.quota.lock().unwrap().snapshot(&slot.id))
+            .collect()
+    }
+
+    pub fn provider_health(&self) -> Vec<ProviderHealthSnapshot> {
+        self.providers
+            .iter()
+            .map(|slot| {
+                slot.health
+                    .lock()
+                    .unwrap()
+                    .snapshot(&slot.id, &self.breaker)
+            })
+            .collect()
+    }
+
+    /// Detects the language of `text`, trusting the local script heuristic
+    /// for unambiguous scripts and asking providers otherwise (e.g. Latin).
+    pub async fn detect_language(&self, text: &str) -> Option<Detection> {
+        let guess = guess_script(text)?;
+
+        if let Some(detection) = guess.confident_detection() {
+            return Some(detection);
+        }
+
+        let chars = text.chars().count() as u64;
+
+        for slot in &self.providers {
+            let provider = &slot.provider;
+
+            if !self.acquire(slot, chars, 1) {
+                continue;
+            }
+
+            match provider.detect(text).await {
+                Ok(Some(detection)) => {
+                    slot.health.lock().unwrap().record_success();
+                    self.record_usage(slot, chars).await;
+                    return Some(detection);
+                }
+                Ok(None) => {
+                    // No detection endpoint; nothing was sent, so give back
+                    // the half-open probe slot and the quota reservation.
+                    slot.health.lock().unwrap().release();
+                    slot.quota.lock().unwrap().release(chars, 1);
+                }
+                Err(e) => {
+                    slot.quota.lock().unwrap().refund(chars);
+                    slot.health
+                        .lock()
+                        .unwrap()
+                        .record_failure(&e.to_string(), &self.breaker);
+                    tracing::warn!("Language detection via {} failed: {}", provider.name(), e);
+                }
+            }
+        }
+
+        guess.detection()
     }
 
     pub async fn translate(&self, request: &TranslationRequest) -> Result<TranslationResponse> {
+        let Some(cache) = &self.cache else {
+            return self.translate_uncached(request).await;
+        };
+
+        let key = CacheKey::from_request(request);
+        if let Some(response) = cache.get(&key).await {
+            tracing::debug!("Translation cache hit ({} → {})", key.source_lang, key.target_lang);
+            return Ok(response);
+        }
+
+        let response = self.translate_uncached(request).await?;
+        cache.insert(key, &response).await;
+
+        Ok(response)
+    }
+
+    async fn translate_uncached(&self, request: &TranslationRequest) -> Result<TranslationResponse> {
         if self.providers.is_empty() {
             return Err(anyhow!("No translation providers configured"));
         }
 
         let mut last_error = None;
+        let mut skipped_open = 0;
+        let mut skipped_limited = 0;
+        let chars = request.text.chars().count() as u64;
+
+        for slot in &self.providers {
+            let provider = &slot.provider;
 
-        for provider in &self.providers {
-            if provider.supports_language(&request.source_lang)
-                && provider.supports_language(&request.target_lang)
+            if !provider.supports_language(&request.source_lang)
+                || !provider.supports_language(&request.target_lang)
+                || (provider.is_llm() && !request.allow_llm)
             {
-                match provider.translate(request).await {
-                    Ok(response) => {
-                        tracing::info!(
-                            "Translation successful using provider: {}",
-                            provider.name()
-                        );
-                        return Ok(response);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Provider {} failed: {}. Trying next provider...",
-                            provider.name(),
-                            e
-                        );
-                        last_error = Some(e);
-                        continue;
-                    }
+                continue;
+            }
+
+            if !slot.health.lock().unwrap().try_acquire(&self.breaker) {
+                tracing::debug!("Skipping provider {}: circuit open", provider.name());
+                skipped_open += 1;
+                continue;
+            }
+
+            let chunks = Self::chunks_for(provider.as_ref(), &request.text);
+            let calls = chunks.as_ref().map_or(1, |chunks| {
+                chunks.iter().filter(|chunk| !chunk.trim().is_empty()).count() as u32
+            }) * provider.calls_per_chunk(request);
+
+            if !slot.quota.lock().unwrap().try_acquire(chars, calls) {
+                tracing::debug!(
+                    "Skipping provider {}: rate limited or out of daily quota",
+                    provider.name()
+                );
+                slot.health.lock().unwrap().release();
+                skipped_limited += 1;
+                continue;
+            }
+
+            match Self::translate_with_provider(provider.as_ref(), request, chunks).await {
+                Ok(mut response) => {
+                    response.provider = Some(provider.name().to_string());
+                    slot.health.lock().unwrap().record_success();
+                    self.record_usage(slot, chars).await;
+                    tracing::info!(
+                        "Translation successful using provider: {}",
+                        provider.name()
+                    );
+                    return Ok(response);
+                }
+                Err(e) => {
+                    slot.quota.lock().unwrap().refund(chars);
+                    slot.health
+                        .lock()
+                        .unwrap()
+                        .record_failure(&e.to_string(), &self.breaker);
+                    tracing::warn!(
+                        "Provider {} failed: {}. Trying next provider...",
+                        provider.name(),
+                        e
+                    );
+                    last_error = Some(e);
                 }
             }
         }
 
+        if last_error.is_none() && skipped_limited > 0 {
+            return Err(anyhow!(
+                "All translation providers are temporarily unavailable (rate limited or out of daily quota)"
+            ));
+        }
+
+        if last_error.is_none() && skipped_open > 0 {
+            return Err(anyhow!(
+                "All translation providers are temporarily unavailable (circuit open)"
+            ));
+        }
+
         Err(last_error.unwrap_or_else(|| anyhow!("All translation providers failed")))
     }
 
-    pub async fn translate_with_fallback(
+    /// Takes the breaker slot and the quota reservation for provider calls,
+    /// or neither when either is unavailable.
+    fn acquire(&self, slot: &ProviderSlot, chars: u64, calls: u32) -> bool {
+        if !slot.health.lock().unwrap().try_acquire(&self.breaker) {
+            return false;
+        }
+
+        if !slot.quota.lock().unwrap().try_acquire(chars, calls) {
+            slot.health.lock().unwrap().release();
+            return false;
+        }
+
+        true
+    }
+
+    /// Persists usage the quota already reserved in `acquire`.
+    async fn record_usage(&self, slot: &ProviderSlot, chars: u64) {
+        if let Some(db) = &self.usage_store {
+            let today = Utc::now().date_naive();
+            if let Err(e) = UsageRepository::add(db.pool(), &slot.id, today, chars as i64).await {
+                tracing::warn!("Failed to persist usage for {}: {}", slot.id, e);
+            }
+        }
+    }
+
+    /// The pieces `text` must be sent in, or `None` when the provider takes
+    /// it in one call.
+    fn chunks_for(provider: &dyn TranslationProvider, text: &str) -> Option<Vec<String>> {
+        match provider.max_text_len() {
+            Some((limit, unit)) if unit.len(text) > limit => Some(split_text(text, limit, unit)),
+            _ => None,
+        }
+    }
+
+    async fn translate_with_provider(
+        provider: &dyn TranslationProvider,
+        request: &TranslationRequest,
+        chunks: Option<Vec<String>>,
+    ) -> Result<TranslationResponse> {
+        let Some(chunks) = chunks else {
+            return provider.translate(request).await;
+        };
+        tracing::debug!(
+            "Splitting {} bytes of text into {} chunks for {}",
+            request.text.len(),
+            chunks.len(),
+            provider.name()
+        );
+
+        let mut translated = String::new();
+        let mut first: Option<TranslationResponse> = None;
+
+        for chunk in chunks {
+            let trimmed = chunk.trim();
+            if trimmed.is_empty() {
+                translated.push_str(&chunk);
+                continue;
+            }
+
+            let response = provider
+                .translate(&TranslationRequest {
+                    text: trimmed.to_string(),
+                    ..request.clone()
+                })
+                .await?;
+
+            translated.push_str(response.translated_text.trim());
+            // Keep the separator the chunk ended with (space or newline).
+            translated.push_str(&chunk[chunk.trim_end().len()..]);
+            first.get_or_insert(response);
+        }
+
+        let first = first.ok_or_else(|| anyhow!("Nothing to translate"))?;
+
+        Ok(TranslationResponse {
+            translated_text: translated.trim_end().to_string(),
+            ..first
+        })
+    }
+
+    /// Translates user text, masking Discord markup around the provider call.
+    pub async fn translate_text(
         &self,
         text: &str,
         source_lang: &str,
         target_lang: &str,
-    ) -> Result<String> {
+        allow_llm: bool,
+    ) -> Result<TranslationResponse> {
+        let masked = MaskedText::mask(text);
+
         let request = TranslationRequest {
-            text: text.to_string(),
+            text: masked.text.clone(),
             source_lang: source_lang.to_string(),
             target_lang: target_lang.to_string(),
+            allow_llm,
         };
 
-        let response = self.translate(&request).await?;
+        let mut response = self.translate(&request).await?;
+
+        if masked.has_spans() {
+            response.translated_text = masked.restore(&response.translated_text);
+        }
+
+        Ok(response)
+    }
+
+    pub async fn translate_with_fallback(
+        &self,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+        allow_llm: bool,
+    ) -> Result<String> {
+        let response = self
+            .translate_text(text, source_lang, target_lang, allow_llm)
+            .await?;
         Ok(response.translated_text)
     }
 }
@@ -0,0 +1,466 @@
+use crate::models::OutputFormat;
+use crate::services::chunker::{split_message, split_text, Unit, DISCORD_MESSAGE_LIMIT};
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const WEBHOOK_USERNAME: &str = "MegaChinese Translation";
+const WEBHOOK_USERNAME_LIMIT: usize = 80;
+const EMBED_FIELD_LIMIT: usize = 1024;
+const EMBED_TOTAL_LIMIT: usize = 6000;
+const EMBED_MAX_FIELDS: usize = 25;
+const EMBED_COLOR: u32 = 0xE0_30_30;
+const EMBED_TITLE: &str = "Jump to message";
+
+/// Everything needed to render one translated message, independent of the
+/// output format.
+#[derive(Debug, Clone)]
+pub struct TranslationLog {
+    pub author_name: String,
+    /// Server nickname or global display name, used when impersonating.
+    pub display_name: String,
+    pub author_id: u64,
+    pub avatar_url: Option<String>,
+    pub original: String,
+    pub translated: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub provider: Option<String>,
+    pub jump_url: String,
+    pub timestamp: String,
+}
+
+pub struct WebhookClient {
+    client: reqwest::Client,
+}
+
+impl WebhookClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Posts a translation and returns the ids of the webhook messages it
+    /// took, so they can follow later edits and deletes of the source.
+    pub async fn send(
+        &self,
+        webhook_url: &str,
+        format: OutputFormat,
+        log: &TranslationLog,
+    ) -> Result<Vec<u64>> {
+        let mut message_ids = Vec::new();
+
+        // Posted in order so a long translation reads top to bottom.
+        for payload in render(format, log) {
+            message_ids.push(self.execute(webhook_url, payload).await?);
+        }
+
+        Ok(message_ids)
+    }
+
+    /// Rewrites previously posted messages in place. Extra pieces are posted,
+    /// surplus old ones deleted, and the resulting message ids returned.
+    pub async fn edit(
+        &self,
+        webhook_url: &str,
+        format: OutputFormat,
+        log: &TranslationLog,
+        message_ids: &[u64],
+    ) -> Result<Vec<u64>> {
+        let payloads = render(format, log);
+        let kept = payloads.len().min(message_ids.len());
+        let mut updated_ids = Vec::new();
+
+        for (index, payload) in payloads.into_iter().enumerate() {
+            match message_ids.get(index) {
+                Some(&message_id) => {
+                    self.patch(webhook_url, message_id, payload).await?;
+                    updated_ids.push(message_id);
+                }
+                None => updated_ids.push(self.execute(webhook_url, payload).await?),
+            }
+        }
+
+        self.delete(webhook_url, &message_ids[kept..]).await?;
+
+        Ok(updated_ids)
+    }
+
+    /// Deletes posted messages; ones that are already gone are ignored.
+    pub async fn delete(&self, webhook_url: &str, message_ids: &[u64]) -> Result<()> {
+        for &message_id in message_ids {
+            let response = self
+                .client
+                .delete(message_url(webhook_url, message_id))
+                .send()
+                .await?;
+
+            if response.status() != reqwest::StatusCode::NOT_FOUND {
+                response.error_for_status()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&self, webhook_url: &str, mut payload: Value) -> Result<u64> {
+        if payload.get("username").is_none() {
+            payload["username"] = json!(WEBHOOK_USERNAME);
+        }
+        // Translated text must never ping anyone.
+        payload["allowed_mentions"] = json!({ "parse": [] });
+
+        let separator = if webhook_url.contains('?') { '&' } else { '?' };
+        let message: WebhookMessage = self
+            .client
+            .post(format!("{}{}wait=true", webhook_url, separator))
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(message.id.parse()?)
+    }
+
+    async fn patch(&self, webhook_url: &str, message_id: u64, mut payload: Value) -> Result<()> {
+        // The author of a webhook message is fixed once it is posted.
+        if let Some(object) = payload.as_object_mut() {
+            object.remove("username");
+            object.remove("avatar_url");
+        }
+        payload["allowed_mentions"] = json!({ "parse": [] });
+
+        self.client
+            .patch(message_url(webhook_url, message_id))
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct WebhookMessage {
+    id: String,
+}
+
+/// `{webhook}/messages/{id}`, keeping any query string such as `thread_id`.
+fn message_url(webhook_url: &str, message_id: u64) -> String {
+    match webhook_url.split_once('?') {
+        Some((base, query)) => format!(
+            "{}/messages/{}?{}",
+            base.trim_end_matches('/'),
+            message_id,
+            query
+        ),
+        None => format!(
+            "{}/messages/{}",
+            webhook_url.trim_end_matches('/'),
+            message_id
+        ),
+    }
+}
+
+/// One webhook payload per message the translation needs.
+fn render(format: OutputFormat, log: &TranslationLog) -> Vec<Value> {
+    match format {
+        OutputFormat::Plain => {
+            let content = format!(
+                "{} (ID: {}) sent this:\n{}\n\nWhich translates to this:\n{}",
+                log.author_name, log.author_id, log.original, log.translated
+            );
+
+            split_message(&content, DISCORD_MESSAGE_LIMIT)
+                .into_iter()
+                .map(|piece| json!({ "content": piece }))
+                .collect()
+        }
+        OutputFormat::Embed => build_embeds(log)
+            .into_iter()
+            .map(|embed| json!({ "embeds": [embed] }))
+            .collect(),
+        OutputFormat::Impersonate => {
+            let username = webhook_username(&log.display_name);
+
+            split_message(&log.translated, DISCORD_MESSAGE_LIMIT)
+                .into_iter()
+                .map(|piece| {
+                    let mut payload = json!({ "content": piece, "username": username });
+                    if let Some(avatar_url) = &log.avatar_url {
+                        payload["avatar_url"] = json!(avatar_url);
+                    }
+                    payload
+                })
+                .collect()
+        }
+    }
+}
+
+impl Default for WebhookClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Discord rejects webhook usernames containing "discord" or longer than 80
+/// characters, so display names are cleaned up before being borrowed.
+fn webhook_username(display_name: &str) -> String {
+    let mut name = display_name.trim().to_string();
+
+    while let Some(start) = name.to_ascii_lowercase().find("discord") {
+        name.replace_range(start..start + "discord".len(), "d1scord");
+    }
+
+    let name: String = name.chars().take(WEBHOOK_USERNAME_LIMIT).collect();
+    let name = name.trim();
+
+    if name.is_empty() || name.eq_ignore_ascii_case("everyone") || name.eq_ignore_ascii_case("here")
+    {
+        WEBHOOK_USERNAME.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Lays the original and translated text out as embed fields, continuing
+/// into further embeds when Discord's per-embed limits would be exceeded.
+/// The first embed carries the author and jump link, the last the footer.
+fn build_embeds(log: &TranslationLog) -> Vec<Value> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    for (label, text) in [
+        ("Original", &log.original),
+        ("Translation", &log.translated),
+    ] {
+        for (index, part) in split_text(text, EMBED_FIELD_LIMIT, Unit::Chars)
+            .into_iter()
+            .enumerate()
+        {
+            let name = if index == 0 {
+                label.to_string()
+            } else {
+                format!("{} (cont.)", label)
+            };
+            let value = if part.trim().is_empty() {
+                "\u{200b}".to_string()
+            } else {
+                part
+            };
+            fields.push((name, value));
+        }
+    }
+
+    let footer = match &log.provider {
+        Some(provider) => format!(
+            "{} → {} • {}",
+            log.source_language, log.target_language, provider
+        ),
+        None => format!("{} → {}", log.source_language, log.target_language),
+    };
+
+    let author = format!("{} ({})", log.author_name, log.author_id);
+    // Every embed is budgeted as if it carried the author, title and footer.
+    let reserved =
+        author.chars().count() + EMBED_TITLE.chars().count() + footer.chars().count();
+    let mut embeds: Vec<Vec<Value>> = vec![Vec::new()];
+    let mut used = reserved;
+
+    for (name, value) in fields {
+        let size = name.chars().count() + value.chars().count();
+        let current = embeds.last_mut().unwrap();
+
+        if !current.is_empty()
+            && (used + size > EMBED_TOTAL_LIMIT || current.len() == EMBED_MAX_FIELDS)
+        {
+            embeds.push(Vec::new());
+            used = reserved;
+        }
+
+        embeds
+            .last_mut()
+            .unwrap()
+            .push(json!({ "name": name, "value": value, "inline": false }));
+        used += size;
+    }
+
+    let count = embeds.len();
+    embeds
+        .into_iter()
+        .enumerate()
+        .map(|(index, fields)| {
+            let mut embed = json!({ "color": EMBED_COLOR, "fields": fields });
+
+            if index == 0 {
+                let mut author = json!({
+                    "name": author,
+                    "url": log.jump_url,
+                });
+                if let Some(avatar_url) = &log.avatar_url {
+                    author["icon_url"] = json!(avatar_url);
+                }
+                embed["author"] = author;
+                embed["title"] = json!(EMBED_TITLE);
+                embed["url"] = json!(log.jump_url);
+            }
+
+            if index + 1 == count {
+                embed["footer"] = json!({ "text": footer });
+                embed["timestamp"] = json!(log.timestamp);
+            }
+
+            embed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(original: &str, translated: &str) -> TranslationLog {
+        TranslationLog {
+            author_name: "someone".to_string(),
+            display_name: "Someone".to_string(),
+            author_id: 123_456_789_012_345_678,
+            avatar_url: Some("https://cdn.example/avatar.png".to_string()),
+            original: original.to_string(),
+            translated: translated.to_string(),
+            source_language: "zh".to_string(),
+            target_language: "en".to_string(),
+            provider: Some("DeepL".to_string()),
+            jump_url: "https://discord.com/channels/1/2/3".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn text(value: &Value, pointer: &str) -> usize {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map_or(0, |text| text.chars().count())
+    }
+
+    /// Characters Discord counts towards the 6000 per-embed total.
+    fn embed_size(embed: &Value) -> usize {
+        let fields: usize = embed["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| text(field, "/name") + text(field, "/value"))
+            .sum();
+
+        fields + text(embed, "/title") + text(embed, "/author/name") + text(embed, "/footer/text")
+    }
+
+    #[test]
+    fn embeds_stay_within_discord_limits() {
+        let original = "这是一个很长的句子。".repeat(1500);
+        let translated = "This is a rather long sentence. ".repeat(600);
+        let log = log(&original, &translated);
+
+        let payloads = render(OutputFormat::Embed, &log);
+        assert!(payloads.len() > 1);
+
+        let embeds: Vec<&Value> = payloads
+            .iter()
+            .map(|payload| {
+                let embeds = payload["embeds"].as_array().unwrap();
+                assert_eq!(embeds.len(), 1);
+                &embeds[0]
+            })
+            .collect();
+
+        for embed in &embeds {
+            let fields = embed["fields"].as_array().unwrap();
+            assert!(!fields.is_empty() && fields.len() <= EMBED_MAX_FIELDS);
+            assert!(fields
+                .iter()
+                .all(|field| text(field, "/value") <= EMBED_FIELD_LIMIT));
+            assert!(embed_size(embed) <= EMBED_TOTAL_LIMIT);
+        }
+
+        // Author and link on the first embed only, footer on the last only.
+        assert!(embeds[0].get("author").is_some());
+        assert!(embeds[1..].iter().all(|embed| embed.get("author").is_none()));
+        assert!(embeds.last().unwrap().get("footer").is_some());
+        assert!(embeds[..embeds.len() - 1]
+            .iter()
+            .all(|embed| embed.get("footer").is_none()));
+
+        // Field values concatenate back to the full texts.
+        let joined = |label: &str| -> String {
+            embeds
+                .iter()
+                .flat_map(|embed| embed["fields"].as_array().unwrap())
+                .filter(|field| field["name"].as_str().unwrap().starts_with(label))
+                .map(|field| field["value"].as_str().unwrap())
+                .collect()
+        };
+        assert_eq!(joined("Original"), original);
+        assert_eq!(joined("Translation"), translated);
+    }
+
+    #[test]
+    fn short_embed_is_a_single_message() {
+        let payloads = render(OutputFormat::Embed, &log("你好", "Hello"));
+
+        assert_eq!(payloads.len(), 1);
+        let embed = &payloads[0]["embeds"][0];
+        assert_eq!(embed["fields"][0]["value"], "你好");
+        assert_eq!(embed["fields"][1]["value"], "Hello");
+        assert_eq!(embed["footer"]["text"], "zh → en • DeepL");
+    }
+
+    #[test]
+    fn plain_output_splits_at_the_message_limit() {
+        let payloads = render(OutputFormat::Plain, &log("你好", "Hello"));
+        assert_eq!(payloads.len(), 1);
+        let content = payloads[0]["content"].as_str().unwrap();
+        assert!(content.contains("你好") && content.contains("Hello"));
+        assert!(payloads[0].get("username").is_none());
+
+        let long = "word ".repeat(1000);
+        let payloads = render(OutputFormat::Plain, &log("你好", &long));
+        assert!(payloads.len() > 1);
+        assert!(payloads
+            .iter()
+            .all(|payload| text(payload, "/content") <= DISCORD_MESSAGE_LIMIT));
+    }
+
+    #[test]
+    fn impersonate_posts_only_the_translation_as_the_author() {
+        let long = "word ".repeat(1000);
+        let payloads = render(OutputFormat::Impersonate, &log("你好", &long));
+
+        assert!(payloads.len() > 1);
+        let content: String = payloads
+            .iter()
+            .map(|payload| payload["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(content.split_whitespace().count(), 1000);
+
+        for payload in &payloads {
+            assert!(text(payload, "/content") <= DISCORD_MESSAGE_LIMIT);
+            assert_eq!(payload["username"], "Someone");
+            assert_eq!(payload["avatar_url"], "https://cdn.example/avatar.png");
+        }
+    }
+
+    #[test]
+    fn webhook_usernames_are_cleaned_up() {
+        assert_eq!(webhook_username("  Alice  "), "Alice");
+        assert_eq!(webhook_username("My Discord Bot"), "My d1scord Bot");
+        assert_eq!(webhook_username("DISCORDdiscord"), "d1scordd1scord");
+        assert_eq!(webhook_username(""), WEBHOOK_USERNAME);
+        assert_eq!(webhook_username("everyone"), WEBHOOK_USERNAME);
+        assert_eq!(webhook_username("Here"), WEBHOOK_USERNAME);
+
+        let long = "名".repeat(100);
+        assert_eq!(webhook_username(&long).chars().count(), WEBHOOK_USERNAME_LIMIT);
+    }
+}
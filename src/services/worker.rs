@@ -0,0 +1,244 @@
+use crate::db::{DatabasePool, HistoryRepository, JobRepository};
+use crate::models::{
+    ChannelMode, TranslationChannel, TranslationHistory, TranslationJob, TranslationOutput,
+};
+use crate::services::channels::CachedChannel;
+use crate::services::detect::{guess_script, is_auto, same_language};
+use crate::services::filter::SkipReason;
+use crate::services::translator::TranslationResponse;
+use crate::services::webhook::{TranslationLog, WebhookClient};
+use crate::services::{ChannelConfigCache, GuildSettingsCache, TranslationService};
+use futures::future::join_all;
+use serenity::all::{ChannelId, GuildId, Http};
+use std::sync::Arc;
+
+/// Translates queued messages and posts them to every output of their
+/// channel.
+pub struct TranslationWorker {
+    db: DatabasePool,
+    http: Arc<Http>,
+    translation_service: Arc<TranslationService>,
+    channels: Arc<ChannelConfigCache>,
+    settings: Arc<GuildSettingsCache>,
+    webhook: WebhookClient,
+}
+
+impl TranslationWorker {
+    pub fn new(
+        db: DatabasePool,
+        http: Arc<Http>,
+        translation_service: Arc<TranslationService>,
+        channels: Arc<ChannelConfigCache>,
+        settings: Arc<GuildSettingsCache>,
+    ) -> Self {
+        Self {
+            db,
+            http,
+            translation_service,
+            channels,
+            settings,
+            webhook: WebhookClient::new(),
+        }
+    }
+
+    pub async fn process(&self, job: &TranslationJob) {
+        let channel_id = ChannelId::new(job.channel_id as u64);
+
+        // The channel may have been removed while the job was waiting.
+        let Some(CachedChannel {
+            config,
+            outputs: extra_outputs,
+        }) = self.channels.get(channel_id)
+        else {
+            return;
+        };
+
+        let detected = self.detect_language(&job.content, &config).await;
+        let bidirectional = config.mode() == ChannelMode::Bidirectional;
+
+        let source_language = match &detected {
+            Some(language) if bidirectional || is_auto(&config.source_language) => language.clone(),
+            _ => config.source_language.clone(),
+        };
+
+        let mut primary = config.primary_output();
+        if bidirectional
+            && detected
+                .as_deref()
+                .is_some_and(|language| same_language(language, &config.target_language))
+        {
+            primary.target_language = config.source_language.clone();
+        }
+
+        let mut outputs = vec![primary];
+        outputs.extend(extra_outputs);
+        outputs.retain(|output| {
+            !detected
+                .as_deref()
+                .is_some_and(|language| same_language(language, &output.target_language))
+        });
+
+        if outputs.is_empty() {
+            self.record_skip(job, SkipReason::AlreadyTargetLanguage)
+                .await;
+            return;
+        }
+
+        let typing = channel_id.start_typing(&self.http);
+        let allow_llm = self
+            .settings
+            .get(GuildId::new(job.guild_id as u64))
+            .await
+            .llm_enabled;
+
+        join_all(
+            outputs
+                .iter()
+                .map(|output| self.deliver(job, &source_language, output, allow_llm)),
+        )
+        .await;
+
+        typing.stop();
+    }
+
+    /// Best guess at the language a message is written in. Auto and
+    /// bidirectional channels ask the detection chain; fixed-source channels
+    /// only use the local heuristic, which is reliable for non-Latin scripts
+    /// and costs no provider call.
+    async fn detect_language(&self, content: &str, config: &TranslationChannel) -> Option<String> {
+        if config.mode() == ChannelMode::Bidirectional || is_auto(&config.source_language) {
+            return self
+                .translation_service
+                .detect_language(content)
+                .await
+                .map(|detection| detection.language);
+        }
+
+        guess_script(content)
+            .and_then(|guess| guess.detection())
+            .map(|detection| detection.language)
+    }
+
+    async fn deliver(
+        &self,
+        job: &TranslationJob,
+        source_language: &str,
+        output: &TranslationOutput,
+        allow_llm: bool,
+    ) {
+        let response = match self
+            .translation_service
+            .translate_text(
+                &job.content,
+                source_language,
+                &output.target_language,
+                allow_llm,
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::error!(
+                    "Translation to {} failed for channel {}: {}",
+                    output.target_language,
+                    job.channel_id,
+                    e
+                );
+                return;
+            }
+        };
+
+        let log = translation_log(job, response, source_language, &output.target_language);
+
+        let message_ids = match self
+            .webhook
+            .send(&output.webhook_url, output.output_format(), &log)
+            .await
+        {
+            Ok(message_ids) => message_ids,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to post {} translation for channel {}: {}",
+                    output.target_language,
+                    job.channel_id,
+                    e
+                );
+                return;
+            }
+        };
+
+        let history = TranslationHistory::new(
+            job.guild_id,
+            job.channel_id,
+            job.author_id,
+            log.original,
+            log.translated,
+            log.source_language,
+            log.target_language,
+        )
+        .with_delivery(
+            job.message_id,
+            output.webhook_url.clone(),
+            output.output_format(),
+            &message_ids,
+        );
+
+        // Edit and delete sync find the posted messages through this row.
+        if let Err(e) = HistoryRepository::create(self.db.pool(), &history).await {
+            tracing::error!(
+                "Failed to record {} translation of message {}: {}",
+                output.target_language,
+                job.message_id,
+                e
+            );
+        }
+    }
+
+    /// Clears a persisted job once it has been handled or dropped.
+    pub async fn finish(&self, job: &TranslationJob) {
+        if !job.is_persisted() {
+            return;
+        }
+
+        if let Err(e) = JobRepository::delete(self.db.pool(), job.id).await {
+            tracing::warn!("Failed to clear finished job {}: {}", job.id, e);
+        }
+    }
+
+    pub async fn record_skip(&self, job: &TranslationJob, reason: SkipReason) {
+        tracing::debug!("Skipping message {}: {}", job.message_id, reason.as_str());
+
+        if let Err(e) = HistoryRepository::record_skip(
+            self.db.pool(),
+            job.guild_id,
+            job.channel_id,
+            reason.as_str(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to record skipped message: {}", e);
+        }
+    }
+}
+
+/// Builds the webhook log for a translated message.
+pub fn translation_log(
+    job: &TranslationJob,
+    response: TranslationResponse,
+    source_language: &str,
+    target_language: &str,
+) -> TranslationLog {
+    TranslationLog {
+        author_name: job.author_name.clone(),
+        display_name: job.display_name.clone(),
+        author_id: job.author_id as u64,
+        avatar_url: job.avatar_url.clone(),
+        original: job.content.clone(),
+        translated: response.translated_text,
+        source_language: source_language.to_string(),
+        target_language: target_language.to_string(),
+        provider: response.provider,
+        jump_url: job.jump_url.clone(),
+        timestamp: job.sent_at.to_rfc3339(),
+    }
+}
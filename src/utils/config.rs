@@ -1,10 +1,191 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub discord_token: String,
     pub database_url: String,
+    pub cache: CacheConfig,
+    pub breaker: BreakerConfig,
+    /// Follow channel config changes made by other instances.
+    pub channel_cache_listen: bool,
+    pub jobs: JobConfig,
+    pub shutdown_timeout: Duration,
+    pub providers: Vec<ProviderConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub ttl: Duration,
+    pub persistent: bool,
+    pub persistent_ttl: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobConfig {
+    pub workers: usize,
+    /// Messages that may wait for a worker before new ones are dropped.
+    pub capacity: usize,
+    /// How many of those may come from a single guild.
+    pub per_guild: usize,
+    pub persistent: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    LibreTranslate,
+    MyMemory,
+    Lingva,
+    DeepL,
+    Google,
+    Llm,
+}
+
+impl ProviderKind {
+    pub fn env_prefix(&self) -> &'static str {
+        match self {
+            ProviderKind::LibreTranslate => "LIBRETRANSLATE",
+            ProviderKind::MyMemory => "MYMEMORY",
+            ProviderKind::Lingva => "LINGVA",
+            ProviderKind::DeepL => "DEEPL",
+            ProviderKind::Google => "GOOGLE",
+            ProviderKind::Llm => "LLM",
+        }
+    }
+}
+
+impl FromStr for ProviderKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "libretranslate" | "libre" => Ok(ProviderKind::LibreTranslate),
+            "mymemory" => Ok(ProviderKind::MyMemory),
+            "lingva" => Ok(ProviderKind::Lingva),
+            "deepl" => Ok(ProviderKind::DeepL),
+            "google" => Ok(ProviderKind::Google),
+            "llm" | "openai" => Ok(ProviderKind::Llm),
+            other => Err(anyhow!("Unknown translation provider: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProviderKind::LibreTranslate => "libretranslate",
+            ProviderKind::MyMemory => "mymemory",
+            ProviderKind::Lingva => "lingva",
+            ProviderKind::DeepL => "deepl",
+            ProviderKind::Google => "google",
+            ProviderKind::Llm => "llm",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One entry of the provider chain. Providers are tried in the order they
+/// appear in `Config::providers`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderConfig {
+    pub kind: ProviderKind,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub formality: Option<String>,
+    #[serde(default)]
+    pub api_version: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default = "default_provider_timeout")]
+    pub timeout_secs: u64,
+    /// Requests per minute; defaults to the public instance's limit where
+    /// one is known, `0` disables limiting.
+    #[serde(default)]
+    pub rate_limit_per_min: Option<u32>,
+    /// Characters per UTC day; defaults to the free tier's allowance where
+    /// one is known, `0` disables the quota.
+    #[serde(default)]
+    pub daily_char_quota: Option<u64>,
+}
+
+impl ProviderConfig {
+    /// Reads `<PREFIX>_ENABLED`, `<PREFIX>_URL`, `<PREFIX>_API_KEY`,
+    /// `<PREFIX>_TIMEOUT_SECS`, `<PREFIX>_RATE_LIMIT_PER_MIN`,
+    /// `<PREFIX>_DAILY_CHAR_QUOTA` and the provider-specific options
+    /// (`_EMAIL`, `_FORMALITY`, `_API_VERSION`, `_PROJECT_ID`, `_FORMAT`,
+    /// `_MODEL`, `_TEMPERATURE`, `_SYSTEM_PROMPT`).
+    pub fn from_env(kind: ProviderKind) -> Result<Self> {
+        let prefix = kind.env_prefix();
+
+        Ok(Self {
+            kind,
+            enabled: env_or(&format!("{}_ENABLED", prefix), true)?,
+            base_url: env_opt(&format!("{}_URL", prefix)),
+            api_key: env_opt(&format!("{}_API_KEY", prefix)),
+            email: env_opt(&format!("{}_EMAIL", prefix)),
+            formality: env_opt(&format!("{}_FORMALITY", prefix)),
+            api_version: env_opt(&format!("{}_API_VERSION", prefix)),
+            project_id: env_opt(&format!("{}_PROJECT_ID", prefix)),
+            format: env_opt(&format!("{}_FORMAT", prefix)),
+            model: env_opt(&format!("{}_MODEL", prefix)),
+            temperature: env_opt(&format!("{}_TEMPERATURE", prefix))
+                .map(|value| value.parse())
+                .transpose()
+                .with_context(|| format!("{}_TEMPERATURE must be a number", prefix))?,
+            system_prompt: env_opt(&format!("{}_SYSTEM_PROMPT", prefix)),
+            timeout_secs: env_or(&format!("{}_TIMEOUT_SECS", prefix), default_provider_timeout())?,
+            rate_limit_per_min: env_opt(&format!("{}_RATE_LIMIT_PER_MIN", prefix))
+                .map(|value| value.parse())
+                .transpose()
+                .with_context(|| format!("{}_RATE_LIMIT_PER_MIN must be a whole number", prefix))?,
+            daily_char_quota: env_opt(&format!("{}_DAILY_CHAR_QUOTA", prefix))
+                .map(|value| value.parse())
+                .transpose()
+                .with_context(|| format!("{}_DAILY_CHAR_QUOTA must be a whole number", prefix))?,
+        })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+const DEFAULT_PROVIDER_CHAIN: &str = "libretranslate,mymemory,lingva";
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_provider_timeout() -> u64 {
+    30
 }
 
 impl Config {
@@ -17,9 +198,82 @@ impl Config {
         let database_url = env::var("DATABASE_URL")
             .context("DATABASE_URL must be set in environment")?;
 
+        let cache = CacheConfig {
+            enabled: env_or("CACHE_ENABLED", true)?,
+            capacity: env_or("CACHE_CAPACITY", 10_000)?,
+            ttl: Duration::from_secs(env_or("CACHE_TTL_SECS", 3_600)?),
+            persistent: env_or("CACHE_PERSISTENT", false)?,
+            persistent_ttl: Duration::from_secs(env_or("CACHE_PERSISTENT_TTL_SECS", 604_800)?),
+        };
+
+        let breaker = BreakerConfig {
+            failure_threshold: env_or("BREAKER_FAILURE_THRESHOLD", 3)?,
+            cooldown: Duration::from_secs(env_or("BREAKER_COOLDOWN_SECS", 60)?),
+        };
+
+        let channel_cache_listen = env_or("CHANNEL_CACHE_LISTEN", false)?;
+
+        let jobs = JobConfig {
+            workers: env_or("JOB_WORKERS", 4)?,
+            capacity: env_or("JOB_QUEUE_CAPACITY", 1_000)?,
+            per_guild: env_or("JOB_QUEUE_PER_GUILD", 200)?,
+            persistent: env_or("JOB_PERSISTENT", false)?,
+        };
+
+        let shutdown_timeout = Duration::from_secs(env_or("SHUTDOWN_TIMEOUT_SECS", 20)?);
+
+        let providers = Self::load_providers()?;
+
         Ok(Self {
             discord_token,
             database_url,
+            cache,
+            breaker,
+            channel_cache_listen,
+            jobs,
+            shutdown_timeout,
+            providers,
         })
     }
+
+    /// Loads the provider chain from the JSON file named by `PROVIDERS_FILE`
+    /// when set, otherwise from `TRANSLATION_PROVIDERS` plus per-provider
+    /// environment variables.
+    fn load_providers() -> Result<Vec<ProviderConfig>> {
+        if let Some(path) = env_opt("PROVIDERS_FILE") {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read provider config file {}", path))?;
+            return serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid provider config file {}", path));
+        }
+
+        let chain = env_opt("TRANSLATION_PROVIDERS").unwrap_or_else(|| DEFAULT_PROVIDER_CHAIN.to_string());
+
+        chain
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| ProviderConfig::from_env(name.parse()?))
+            .collect()
+    }
+}
+
+fn env_opt(key: &str) -> Option<String> {
+    env::var(key)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn env_or<T>(key: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .with_context(|| format!("{} has an invalid value: {}", key, value)),
+        _ => Ok(default),
+    }
 }
@@ -1,5 +1,6 @@
 pub mod config;
 pub mod permissions;
+pub mod shutdown;
 
 pub use config::Config;
 pub use permissions::PermissionChecker;
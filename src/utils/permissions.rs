@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use serenity::all::{Context, GuildId, Message, UserId};
+use serenity::all::{Context, Member, Message, PartialGuild, Permissions, RoleId};
 
 pub struct PermissionChecker;
 
@@ -10,22 +10,33 @@ impl PermissionChecker {
             .ok_or_else(|| anyhow!("This command can only be used in a server"))?;
 
         let member = guild_id.member(&ctx.http, msg.author.id).await?;
+        let guild = guild_id.to_partial_guild(&ctx.http).await?;
 
-        if Self::is_guild_owner(ctx, guild_id, msg.author.id).await? {
+        if guild.owner_id == msg.author.id {
             return Ok(true);
         }
 
-        let guild = guild_id.to_partial_guild(&ctx.http).await?;
-        let permissions = guild.member_permissions(&member);
+        Ok(Self::grants_admin(Self::guild_permissions(&guild, &member)))
+    }
 
-        Ok(permissions.administrator()
-            || permissions.manage_guild()
-            || permissions.manage_channels())
+    /// Server-wide permissions from `@everyone` and the member's roles.
+    /// Channel overwrites are deliberately ignored: admin commands change
+    /// guild-wide configuration, so a per-channel grant must not unlock them.
+    fn guild_permissions(guild: &PartialGuild, member: &Member) -> Permissions {
+        let everyone = RoleId::new(guild.id.get());
+
+        std::iter::once(&everyone)
+            .chain(member.roles.iter())
+            .filter_map(|role_id| guild.roles.get(role_id))
+            .fold(Permissions::empty(), |permissions, role| {
+                permissions | role.permissions
+            })
     }
 
-    async fn is_guild_owner(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<bool> {
-        let guild = guild_id.to_partial_guild(&ctx.http).await?;
-        Ok(guild.owner_id == user_id)
+    /// The permission set that counts as a bot admin, also used to gate
+    /// slash commands where Discord resolves the member's permissions.
+    pub fn grants_admin(permissions: Permissions) -> bool {
+        permissions.administrator() || permissions.manage_guild() || permissions.manage_channels()
     }
 
     pub async fn require_admin(ctx: &Context, msg: &Message) -> Result<()> {